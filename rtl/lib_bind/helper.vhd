@@ -0,0 +1,12 @@
+LIBRARY IEEE;
+USE ieee.std_logic_1164.all;
+
+ENTITY helper IS
+    PORT (
+        a : IN std_logic
+    );
+END ENTITY helper;
+
+ARCHITECTURE rtl OF helper IS
+BEGIN
+END ARCHITECTURE rtl;
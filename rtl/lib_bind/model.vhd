@@ -0,0 +1,20 @@
+LIBRARY IEEE;
+USE ieee.std_logic_1164.all;
+
+ENTITY model IS
+    PORT (
+        a : IN std_logic
+    );
+END ENTITY model;
+
+ARCHITECTURE rtl OF model IS
+BEGIN
+END ARCHITECTURE rtl;
+
+ARCHITECTURE behav OF model IS
+BEGIN
+    helper_i : ENTITY work.helper
+    PORT MAP(
+        a => a
+    );
+END ARCHITECTURE behav;
@@ -0,0 +1,31 @@
+LIBRARY IEEE;
+USE ieee.std_logic_1164.all;
+
+ENTITY top_bind IS
+END ENTITY top_bind;
+
+ARCHITECTURE sim OF top_bind IS
+    SIGNAL a : std_logic;
+
+    COMPONENT model IS
+        PORT (
+            a : IN std_logic
+        );
+    END COMPONENT model;
+
+    COMPONENT dut IS
+        PORT (
+            a : IN std_logic
+        );
+    END COMPONENT dut;
+BEGIN
+    model_i : model
+    PORT MAP(
+        a => a
+    );
+
+    dut_i : dut
+    PORT MAP(
+        a => a
+    );
+END ARCHITECTURE sim;
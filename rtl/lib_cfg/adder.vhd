@@ -0,0 +1,12 @@
+LIBRARY IEEE;
+USE ieee.std_logic_1164.all;
+
+ENTITY adder IS
+    PORT (
+        a : IN std_logic
+    );
+END ENTITY adder;
+
+ARCHITECTURE rtl OF adder IS
+BEGIN
+END ARCHITECTURE rtl;
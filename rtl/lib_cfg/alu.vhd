@@ -0,0 +1,23 @@
+LIBRARY IEEE;
+USE ieee.std_logic_1164.all;
+
+ENTITY alu IS
+    PORT (
+        a : IN std_logic
+    );
+END ENTITY alu;
+
+ARCHITECTURE rtl OF alu IS
+    COMPONENT adder IS
+        PORT (
+            a : IN std_logic
+        );
+    END COMPONENT adder;
+BEGIN
+    gen_add : FOR i IN 0 TO 1 GENERATE
+        add_i : adder
+        PORT MAP(
+            a => a
+        );
+    END GENERATE gen_add;
+END ARCHITECTURE rtl;
@@ -0,0 +1,5 @@
+-- configuration of an entity which is instantiated, no toplevel
+CONFIGURATION cfg_alu OF alu IS
+    FOR rtl
+    END FOR;
+END CONFIGURATION cfg_alu;
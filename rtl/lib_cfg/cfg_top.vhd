@@ -0,0 +1,49 @@
+LIBRARY IEEE;
+USE ieee.std_logic_1164.all;
+
+ENTITY cfg_top IS
+END ENTITY cfg_top;
+
+ARCHITECTURE sim OF cfg_top IS
+    SIGNAL a : std_logic;
+
+    COMPONENT alu IS
+        PORT (
+            a : IN std_logic
+        );
+    END COMPONENT alu;
+
+    COMPONENT reg IS
+        PORT (
+            a : IN std_logic
+        );
+    END COMPONENT reg;
+
+    FOR reg_i : reg USE ENTITY work.reg(rtl);
+BEGIN
+    gen_alu : IF true GENERATE
+        -- alu_i : reg
+        alu_i : alu
+        PORT MAP(
+            a => a
+        );
+    END GENERATE gen_alu;
+
+    reg_i : reg
+    PORT MAP(
+        a => a
+    );
+
+    blk_alu : BLOCK
+    BEGIN
+        alu2_i : alu
+        PORT MAP(
+            a => a
+        );
+    END BLOCK blk_alu;
+
+    mem_i : ENTITY work.mem
+    PORT MAP(
+        a => a
+    );
+END ARCHITECTURE sim;
@@ -0,0 +1,12 @@
+LIBRARY IEEE;
+USE ieee.std_logic_1164.all;
+
+ENTITY mem IS
+    PORT (
+        a : IN std_logic
+    );
+END ENTITY mem;
+
+ARCHITECTURE rtl OF mem IS
+BEGIN
+END ARCHITECTURE rtl;
@@ -0,0 +1,12 @@
+LIBRARY IEEE;
+USE ieee.std_logic_1164.all;
+
+ENTITY reg IS
+    PORT (
+        a : IN std_logic
+    );
+END ENTITY reg;
+
+ARCHITECTURE rtl OF reg IS
+BEGIN
+END ARCHITECTURE rtl;
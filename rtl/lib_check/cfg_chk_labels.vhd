@@ -0,0 +1,15 @@
+LIBRARY lib_cfg;
+
+CONFIGURATION cfg_chk_labels OF chk_top IS
+    FOR sim
+        FOR alu_x : alu
+            USE ENTITY lib_cfg.alu(rtl);
+        END FOR;
+        FOR reg_i : alu
+            USE ENTITY lib_cfg.reg(rtl);
+        END FOR;
+        FOR mem_i : mem
+            USE ENTITY lib_cfg.mem(rtl);
+        END FOR;
+    END FOR;
+END CONFIGURATION cfg_chk_labels;
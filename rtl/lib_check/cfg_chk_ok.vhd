@@ -0,0 +1,19 @@
+LIBRARY lib_cfg;
+
+CONFIGURATION cfg_chk_ok OF chk_top IS
+    FOR sim
+        FOR alu_i : alu
+            USE ENTITY lib_cfg.alu(rtl);
+            FOR rtl
+                FOR gen_add
+                    FOR add_i : adder
+                        USE ENTITY lib_cfg.adder(rtl);
+                    END FOR;
+                END FOR;
+            END FOR;
+        END FOR;
+        FOR ALL : reg
+            USE ENTITY lib_cfg.reg(rtl);
+        END FOR;
+    END FOR;
+END CONFIGURATION cfg_chk_ok;
@@ -0,0 +1,11 @@
+LIBRARY lib_cfg;
+
+CONFIGURATION cfg_chk_ports OF chk_ports IS
+    FOR sim
+        FOR ALL : alu
+            USE ENTITY lib_cfg.adder(rtl);
+        END FOR;
+        FOR sh_i : shifter
+        END FOR;
+    END FOR;
+END CONFIGURATION cfg_chk_ports;
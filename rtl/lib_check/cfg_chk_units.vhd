@@ -0,0 +1,15 @@
+LIBRARY lib_cfg;
+
+CONFIGURATION cfg_chk_units OF chk_top IS
+    FOR sim
+        FOR alu_i : alu
+            USE ENTITY lib_cfg.alu(fast);
+        END FOR;
+        FOR reg_i : reg
+            USE CONFIGURATION lib_cfg.cfg_reg;
+        END FOR;
+        FOR reg2_i : reg
+            USE ENTITY lib_cfg.regx;
+        END FOR;
+    END FOR;
+END CONFIGURATION cfg_chk_units;
@@ -0,0 +1,15 @@
+LIBRARY IEEE;
+USE ieee.std_logic_1164.all;
+
+PACKAGE chk_pkg IS
+    COMPONENT shifter IS
+        GENERIC (
+            WIDTH : integer
+        );
+        PORT (
+            a : IN std_logic_vector(7 DOWNTO 0); -- constraints aren't compared
+            z : IN std_logic;
+            q : OUT std_logic
+        );
+    END COMPONENT shifter;
+END PACKAGE chk_pkg;
@@ -0,0 +1,40 @@
+LIBRARY IEEE;
+USE ieee.std_logic_1164.all;
+
+LIBRARY lib_cfg;
+
+USE work.chk_pkg.all;
+
+ENTITY chk_ports IS
+END ENTITY chk_ports;
+
+ARCHITECTURE sim OF chk_ports IS
+    SIGNAL a : std_logic;
+    SIGNAL b : bit;
+
+    COMPONENT alu IS
+        PORT (
+            a : IN bit
+        );
+    END COMPONENT alu;
+BEGIN
+    alu_i : alu
+    PORT MAP(
+        a => b
+    );
+
+    alu2_i : alu
+    PORT MAP(
+        a => b
+    );
+
+    sh_i : shifter
+    GENERIC MAP(
+        WIDTH => 8
+    )
+    PORT MAP(
+        a => (OTHERS => a),
+        z => a,
+        q => OPEN
+    );
+END ARCHITECTURE sim;
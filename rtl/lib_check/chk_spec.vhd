@@ -0,0 +1,37 @@
+LIBRARY IEEE;
+USE ieee.std_logic_1164.all;
+
+LIBRARY lib_cfg;
+
+ENTITY chk_spec IS
+END ENTITY chk_spec;
+
+ARCHITECTURE sim OF chk_spec IS
+    SIGNAL a : std_logic;
+    SIGNAL b : bit;
+
+    COMPONENT alu IS
+        PORT (
+            a : IN bit
+        );
+    END COMPONENT alu;
+
+    COMPONENT shifter IS
+        PORT (
+            a : IN std_logic
+        );
+    END COMPONENT shifter;
+
+    FOR ALL : alu USE ENTITY lib_cfg.adder(rtl);
+    FOR sh_i : shifter USE ENTITY lib_cfg.alu;
+BEGIN
+    alu_i : alu
+    PORT MAP(
+        a => b
+    );
+
+    sh_i : shifter
+    PORT MAP(
+        a => a
+    );
+END ARCHITECTURE sim;
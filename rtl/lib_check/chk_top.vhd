@@ -0,0 +1,41 @@
+LIBRARY IEEE;
+USE ieee.std_logic_1164.all;
+
+ENTITY chk_top IS
+END ENTITY chk_top;
+
+ARCHITECTURE sim OF chk_top IS
+    SIGNAL a : std_logic;
+
+    COMPONENT alu IS
+        PORT (
+            a : IN std_logic
+        );
+    END COMPONENT alu;
+
+    COMPONENT reg IS
+        PORT (
+            a : IN std_logic
+        );
+    END COMPONENT reg;
+BEGIN
+    alu_i : alu
+    PORT MAP(
+        a => a
+    );
+
+    reg_i : reg
+    PORT MAP(
+        a => a
+    );
+
+    reg2_i : reg
+    PORT MAP(
+        a => a
+    );
+
+    mem_i : ENTITY lib_cfg.mem
+    PORT MAP(
+        a => a
+    );
+END ARCHITECTURE sim;
@@ -0,0 +1,18 @@
+LIBRARY IEEE;
+USE ieee.std_logic_1164.all;
+
+ENTITY shifter IS
+    GENERIC (
+        WIDTH : integer := 4;
+        DEPTH : natural
+    );
+    PORT (
+        a  : IN  std_logic_vector(WIDTH - 1 DOWNTO 0);
+        z  : OUT std_logic;
+        en : IN  std_logic := '1'
+    );
+END ENTITY shifter;
+
+ARCHITECTURE rtl OF shifter IS
+BEGIN
+END ARCHITECTURE rtl;
@@ -0,0 +1,26 @@
+LIBRARY IEEE;
+USE ieee.std_logic_1164.all;
+
+LIBRARY unisim;
+USE unisim.vcomponents.all;
+
+LIBRARY xpm;
+
+ENTITY top_ext IS
+    PORT (
+        clk : IN  std_logic;
+        d   : IN  std_logic;
+        q   : OUT std_logic
+    );
+END ENTITY top_ext;
+
+ARCHITECTURE rtl OF top_ext IS
+BEGIN
+
+    i_fdre : FDRE
+        PORT MAP (C => clk, CE => '1', R => '0', D => d, Q => open);
+
+    i_cdc : ENTITY xpm.xpm_cdc_single
+        PORT MAP (src_in => d, dest_clk => clk, dest_out => q);
+
+END ARCHITECTURE rtl;
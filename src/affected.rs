@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::process::{exit, Command};
+
+use log::error;
+
+use crate::classes::{BindingOverrides, Library};
+use crate::{get_libraries, get_toplevel_elements};
+
+fn normalize(filename: &str) -> String {
+    match fs::canonicalize(filename) {
+        Ok(path) => path.to_string_lossy().to_string(),
+        Err(_) => filename.to_string(),
+    }
+}
+
+// files changed since rev, as reported by the local git
+pub fn get_git_changed_files(rev: &str) -> Vec<String> {
+    let run = |args: &[&str]| -> String {
+        match Command::new("git").args(args).output() {
+            Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout).to_string(),
+            Ok(output) => {
+                error!("git {} failed: {}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim());
+                exit(1)
+            }
+            Err(err) => {
+                error!("Can't call git: {}", err);
+                exit(1)
+            }
+        }
+    };
+    let root = run(&["rev-parse", "--show-toplevel"]);
+    let root = Path::new(root.trim());
+    run(&["diff", "--name-only", rev])
+        .lines()
+        .filter(|l| !l.is_empty())
+        .map(|l| root.join(l).to_string_lossy().to_string())
+        .collect()
+}
+
+// toplevel candidates of all libraries and the changed files in their compile order
+pub fn get_affected_toplevels(libraries: &HashMap<String, Library>, changed: &[String]) -> Vec<(String, Vec<String>)> {
+    let changed: Vec<String> = changed.iter().map(|f| normalize(f)).collect();
+    let mut names: Vec<&String> = libraries.keys().collect();
+    names.sort();
+    let mut ret: Vec<(String, Vec<String>)> = Vec::new();
+    for lib_name in names {
+        let lib = &libraries[lib_name];
+        if !lib.is_compiled() {
+            continue;
+        }
+        for candidate in lib.list_toplevel_candidates() {
+            let element_list = get_toplevel_elements(libraries, lib_name, &candidate, &BindingOverrides::new());
+            let mut files: Vec<String> = Vec::new();
+            for el in &element_list {
+                let filename = normalize(&el.filename);
+                if changed.contains(&filename) && !files.contains(&filename) {
+                    files.push(filename);
+                }
+            }
+            if !files.is_empty() {
+                ret.push((format!("{}.{}", lib_name, candidate), files));
+            }
+        }
+    }
+    ret
+}
+
+pub fn print_affected(
+    libraries_toml_filename: &String, tool_toml_filename: &String, replacements: &HashMap<String, String>,
+    changed: &[String], git_diff: &str, json: bool,
+) {
+    let mut changed = changed.to_vec();
+    if !git_diff.is_empty() {
+        changed.extend(get_git_changed_files(git_diff));
+    }
+    let libraries = get_libraries(libraries_toml_filename, tool_toml_filename, replacements);
+    let affected = get_affected_toplevels(&libraries, &changed);
+    if json {
+        let toplevels: Vec<serde_json::Value> = affected
+            .iter()
+            .map(|(toplevel, files)| serde_json::json!({"toplevel": toplevel, "files": files}))
+            .collect();
+        let content = serde_json::json!({"changed": changed, "affected": toplevels});
+        println!("{}", serde_json::to_string_pretty(&content).unwrap_or_default());
+    } else {
+        for (toplevel, _) in &affected {
+            println!("{}", toplevel);
+        }
+    }
+}
@@ -0,0 +1,460 @@
+use std::collections::HashMap;
+use std::fs;
+use std::process::exit;
+
+use log::error;
+
+use crate::classes::{Architecture, CheckFinding, Component, Configuration, Entity, Library, RE_COMMENT, RE_CONFIGURATION_HEADER, RE_CONF_TOKEN, RE_ENT};
+use crate::{get_architecture_body, get_libraries};
+use crate::why::find_line;
+
+// block configuration of an architecture, a generate or a block statement
+struct BlockConfig {
+    name: String,
+    components: Vec<ComponentConfig>,
+    blocks: Vec<BlockConfig>,
+}
+
+struct ComponentConfig {
+    labels: Vec<String>,
+    component: String,
+    aspect: String, // [entity, configuration, open], empty without binding indication
+    target: String, // lib.entity(arch) or lib.configuration
+    block: Option<BlockConfig>,
+}
+
+enum Token {
+    Component(ComponentConfig),
+    Block(String),
+    End,
+}
+
+fn get_tokens(content: &str) -> Vec<Token> {
+    let mut ret: Vec<Token> = Vec::new();
+    for caps in RE_CONF_TOKEN.captures_iter(content) {
+        if let Some(block) = caps.name("block") {
+            ret.push(Token::Block(block.as_str().to_string()));
+        } else if let Some(comp) = caps.name("comp") {
+            let (aspect, target) = match (caps.name("open"), caps.name("entity"), caps.name("conf")) {
+                (Some(_), _, _) => ("open", String::new()),
+                (_, Some(e), _) => ("entity", e.as_str().split_whitespace().collect::<String>()),
+                (_, _, Some(c)) => ("configuration", c.as_str().to_string()),
+                _ => ("", String::new()),
+            };
+            ret.push(Token::Component(ComponentConfig {
+                labels: caps["labels"].split(',').map(|l| l.trim().to_string()).collect(),
+                component: comp.as_str().to_string(),
+                aspect: aspect.to_string(),
+                target,
+                block: None,
+            }));
+        } else {
+            ret.push(Token::End);
+        }
+    }
+    ret
+}
+
+fn parse_block(name: &str, tokens: &mut impl Iterator<Item = Token>) -> BlockConfig {
+    let mut block = BlockConfig {
+        name: name.to_string(),
+        components: Vec::new(),
+        blocks: Vec::new(),
+    };
+    while let Some(token) = tokens.next() {
+        match token {
+            Token::Component(mut component) => {
+                // a component configuration holds at most one block configuration before its end
+                if let Some(Token::Block(name)) = tokens.next() {
+                    component.block = Some(parse_block(&name, tokens));
+                    tokens.next();
+                }
+                block.components.push(component);
+            }
+            Token::Block(name) => block.blocks.push(parse_block(&name, tokens)),
+            Token::End => break,
+        }
+    }
+    block
+}
+
+// block configuration of the architecture, read again from the file as the library only keeps a flat map
+fn read_configuration(conf: &Configuration) -> Option<BlockConfig> {
+    let bytes = fs::read(&conf.filename).ok()?;
+    let content = RE_COMMENT.replace_all(&String::from_utf8_lossy(&bytes), "").to_ascii_lowercase();
+    let start = RE_CONFIGURATION_HEADER
+        .captures_iter(&content)
+        .find(|caps| caps["name"].eq_ignore_ascii_case(&conf.name) && caps["entity"].eq_ignore_ascii_case(&conf.entity))?
+        .get(0)?
+        .end();
+    let mut tokens = get_tokens(&content[start..]).into_iter();
+    match tokens.next() {
+        Some(Token::Block(arch)) => Some(parse_block(&arch, &mut tokens)),
+        _ => None,
+    }
+}
+
+// configuration specifications of an architecture, read again from the file as the resolver ignores them
+fn read_specifications(arch: &Architecture) -> Vec<ComponentConfig> {
+    let Ok(bytes) = fs::read(&arch.filename) else {
+        return Vec::new();
+    };
+    let content = RE_COMMENT.replace_all(&String::from_utf8_lossy(&bytes), "").to_ascii_lowercase();
+    let Some(body) = get_architecture_body(&content, &arch.entity, &arch.name) else {
+        return Vec::new();
+    };
+    get_tokens(body)
+        .into_iter()
+        .filter_map(|token| match token {
+            Token::Component(spec) if !spec.aspect.is_empty() => Some(spec),
+            _ => None,
+        })
+        .collect()
+}
+
+// component configurations of the architecture, those of generates and block statements included
+fn get_component_configs(block: &BlockConfig) -> Vec<&ComponentConfig> {
+    let mut ret: Vec<&ComponentConfig> = block.components.iter().collect();
+    for inner in &block.blocks {
+        ret.extend(get_component_configs(inner));
+    }
+    ret
+}
+
+fn label_pattern(label: &str) -> String {
+    format!(r"\bfor\b[\w\s,]*\b{}\b[\w\s,]*:", regex::escape(label))
+}
+
+fn report(findings: &mut Vec<CheckFinding>, conf: &Configuration, pattern: &str, message: String) {
+    let mut finding = CheckFinding::new(&conf.library, &conf.name, &conf.filename, &message);
+    finding.line = find_line(&conf.filename, pattern);
+    findings.push(finding);
+}
+
+// declaration of a component in the architecture or in a package it uses
+fn find_component<'a>(
+    arch: &'a Architecture, lib_name: &str, libraries: &'a HashMap<String, Library>, name: &str,
+) -> Option<&'a Component> {
+    if let Some(component) = arch.components.get(name) {
+        return Some(component);
+    }
+    arch.uses.iter().find_map(|usage| {
+        let mut parts = usage.split('.');
+        let lib = match parts.next()? {
+            "work" => lib_name,
+            lib => lib,
+        };
+        libraries.get(lib)?.packages.get(parts.next()?)?.components.get(name)
+    })
+}
+
+// entity an instance of the component is bound to without configuration, searched like the resolver does
+fn find_default_entity<'a>(
+    arch: &'a Architecture, lib_name: &str, libraries: &'a HashMap<String, Library>, name: &str,
+) -> Option<(String, &'a Entity)> {
+    let mut lib_names: Vec<&str> = vec![lib_name];
+    for usage in &arch.uses {
+        if let Some((lib, _)) = usage.split_once('.') {
+            if lib != "work" && !lib_names.contains(&lib) {
+                lib_names.push(lib);
+            }
+        }
+    }
+    lib_names.into_iter().find_map(|lib| {
+        let entity = &libraries.get(lib)?.designs.get(name)?.entity;
+        (!entity.name.is_empty()).then(|| (lib.to_string(), entity))
+    })
+}
+
+// entity a configuration specification binds, none for open
+fn find_specified_entity<'a>(
+    spec: &ComponentConfig, lib_name: &str, libraries: &'a HashMap<String, Library>,
+) -> Option<(String, &'a Entity)> {
+    let (lib, unit) = spec.target.split_once('.').unwrap_or(("work", &spec.target));
+    let lib = if lib == "work" { lib_name } else { lib };
+    let library = libraries.get(lib)?;
+    let entity_name = match spec.aspect.as_str() {
+        "entity" => unit.split('(').next()?.to_string(),
+        "configuration" => library.configurations.get(unit)?.entity.clone(),
+        _ => return None,
+    };
+    let entity = &library.designs.get(&entity_name)?.entity;
+    (!entity.name.is_empty()).then(|| (lib.to_string(), entity))
+}
+
+// differences between a component declaration and the entity it is bound to
+pub fn compare_interfaces(component: &Component, entity: &Entity) -> Vec<String> {
+    let mut ret: Vec<String> = Vec::new();
+    for (kind, comp_items, entity_items) in [
+        ("generic", &component.generics, &entity.generics),
+        ("port", &component.ports, &entity.ports),
+    ] {
+        for item in entity_items {
+            match comp_items.iter().find(|c| c.name == item.name) {
+                // inputs and generics with default value may be left out
+                None if item.default.is_empty() || (kind == "port" && item.mode != "in") => {
+                    ret.push(format!("{} {} is missing in the component", kind, item.name))
+                }
+                None => {}
+                Some(comp_item) => {
+                    if comp_item.mode != item.mode {
+                        ret.push(format!(
+                            "port {} is {} in the component but {} in the entity",
+                            item.name, comp_item.mode, item.mode
+                        ));
+                    }
+                    if comp_item.type_mark() != item.type_mark() {
+                        ret.push(format!(
+                            "{} {} is {} in the component but {} in the entity",
+                            kind, item.name, comp_item.typ, item.typ
+                        ));
+                    }
+                }
+            }
+        }
+        for item in comp_items.iter().filter(|c| !entity_items.iter().any(|e| e.name == c.name)) {
+            ret.push(format!("{} {} doesn't exist in the entity", kind, item.name));
+        }
+    }
+    ret
+}
+
+// one finding per difference at the location of the instance or component configuration
+fn report_interfaces(
+    findings: &mut Vec<CheckFinding>, location: &CheckFinding, component: &Component, bound: &str, entity: &Entity,
+) {
+    for difference in compare_interfaces(component, entity) {
+        let message = format!("component {} doesn't match entity {}: {}", component.name, bound, difference);
+        let mut finding = CheckFinding::new(&location.library, &location.unit, &location.filename, &message);
+        finding.line = location.line;
+        findings.push(finding);
+    }
+}
+
+fn entity_exists(lib: &Library, entity: &str) -> bool {
+    lib.designs.get(entity).is_some_and(|d| !d.entity.name.is_empty()) || lib.modules.contains_key(entity)
+}
+
+// checks that the bound unit exists, returns the library and entity a block configuration below refers to
+fn check_binding(
+    component: &ComponentConfig, lib_name: &str, conf: &Configuration, libraries: &HashMap<String, Library>,
+    findings: &mut Vec<CheckFinding>,
+) -> Option<(String, String)> {
+    let pattern = label_pattern(&component.labels[0]);
+    if component.aspect.is_empty() {
+        // default binding to the entity with the name of the component
+        return Some((lib_name.to_string(), component.component.clone()));
+    }
+    if component.aspect == "open" {
+        return None;
+    }
+    let caps = RE_ENT.captures(&component.target)?;
+    let lib = match caps.name("lib").map(|m| m.as_str()) {
+        Some(lib) if lib != "work" => lib.to_string(),
+        _ => conf.library.clone(),
+    };
+    let Some(library) = libraries.get(&lib) else {
+        report(findings, conf, &pattern, format!("library {} of {} doesn't exist", lib, component.target));
+        return None;
+    };
+    // units of precompiled and external libraries are unknown
+    if !library.is_compiled() {
+        return None;
+    }
+    let name = caps["entity"].to_string();
+    if component.aspect == "configuration" {
+        if !library.configurations.contains_key(&name) {
+            report(findings, conf, &pattern, format!("configuration {}.{} doesn't exist", lib, name));
+        }
+        return None;
+    }
+    if !entity_exists(library, &name) {
+        report(findings, conf, &pattern, format!("entity {}.{} doesn't exist", lib, name));
+        return None;
+    }
+    if let Some(arch) = caps.name("arch").map(|m| m.as_str()) {
+        if library.designs.get(&name).is_some_and(|d| !d.architectures.contains_key(arch)) {
+            report(findings, conf, &pattern, format!("architecture {} of {}.{} doesn't exist", arch, lib, name));
+        }
+    }
+    Some((lib, name))
+}
+
+fn check_block(
+    block: &BlockConfig, lib_name: &str, entity: &str, conf: &Configuration, libraries: &HashMap<String, Library>,
+    findings: &mut Vec<CheckFinding>,
+) {
+    let unit = format!("{}.{}({})", lib_name, entity, block.name);
+    let Some(arch) = libraries
+        .get(lib_name)
+        .and_then(|l| l.designs.get(entity))
+        .and_then(|d| d.architectures.get(&block.name))
+    else {
+        let pattern = format!(r"\bfor\s+{}\b", regex::escape(&block.name));
+        report(findings, conf, &pattern, format!("architecture {} of {}.{} doesn't exist", block.name, lib_name, entity));
+        return;
+    };
+    let components = get_component_configs(block);
+    for component in &components {
+        for label in &component.labels {
+            let pattern = label_pattern(label);
+            if label == "all" || label == "others" {
+                if !arch.instances.iter().any(|i| i.typ == "component" && i.component == component.component) {
+                    report(findings, conf, &pattern, format!("{} has no instance of component {}", unit, component.component));
+                }
+                continue;
+            }
+            match arch.instances.iter().find(|i| &i.label == label) {
+                None => report(findings, conf, &pattern, format!("label {} doesn't exist in {}", label, unit)),
+                Some(instance) if instance.typ != "component" => report(
+                    findings, conf, &pattern,
+                    format!("{} in {} instantiates {} {} directly and can't be configured", label, unit, instance.typ, instance.component),
+                ),
+                Some(instance) if instance.component != component.component => report(
+                    findings, conf, &pattern,
+                    format!("{} in {} is an instance of component {}, not {}", label, unit, instance.component, component.component),
+                ),
+                Some(_) => {}
+            }
+        }
+        let bound = check_binding(component, lib_name, conf, libraries, findings);
+        if let Some((bound_lib, bound_entity)) = &bound {
+            // default bindings are compared by check_components
+            let entity = libraries.get(bound_lib).and_then(|l| l.designs.get(bound_entity)).map(|d| &d.entity);
+            let declaration = find_component(arch, lib_name, libraries, &component.component);
+            if let (false, Some(entity), Some(declaration)) = (component.aspect.is_empty(), entity, declaration) {
+                let mut location = CheckFinding::new(&conf.library, &conf.name, &conf.filename, "");
+                location.line = find_line(&conf.filename, &label_pattern(&component.labels[0]));
+                report_interfaces(findings, &location, declaration, &format!("{}.{}", bound_lib, bound_entity), entity);
+            }
+        }
+        if let (Some(inner), Some((bound_lib, bound_entity))) = (&component.block, bound) {
+            check_block(inner, &bound_lib, &bound_entity, conf, libraries, findings);
+        }
+    }
+    // a block configuring some of its instances has to configure all of them
+    if components.is_empty() {
+        return;
+    }
+    for instance in arch.instances.iter().filter(|i| i.typ == "component") {
+        let configured = components.iter().any(|c| {
+            c.labels.contains(&instance.label)
+                || (c.component == instance.component && c.labels.iter().any(|l| l == "all" || l == "others"))
+        });
+        if !configured {
+            let pattern = format!(r"\bfor\s+{}\b", regex::escape(&block.name));
+            report(
+                findings, conf, &pattern,
+                format!("instance {} of component {} in {} isn't configured", instance.label, instance.component, unit),
+            );
+        }
+    }
+}
+
+// consistency of the configurations of one or all libraries with the architectures and units they bind
+pub fn check_configurations(libraries: &HashMap<String, Library>, lib_name: &str) -> Vec<CheckFinding> {
+    let mut names: Vec<&String> = libraries
+        .iter()
+        .filter(|(name, lib)| (lib_name.is_empty() || *name == lib_name) && lib.is_compiled())
+        .map(|(name, _)| name)
+        .collect();
+    names.sort();
+    let mut findings: Vec<CheckFinding> = Vec::new();
+    for name in names {
+        let lib = &libraries[name];
+        let mut configurations: Vec<&Configuration> = lib.configurations.values().collect();
+        configurations.sort_by(|a, b| a.name.cmp(&b.name));
+        for conf in configurations {
+            if !entity_exists(lib, &conf.entity) {
+                let pattern = format!(r"\bconfiguration\s+{}\b", regex::escape(&conf.name));
+                report(&mut findings, conf, &pattern, format!("entity {}.{} doesn't exist", name, conf.entity));
+                continue;
+            }
+            match read_configuration(conf) {
+                Some(block) => check_block(&block, name, &conf.entity, conf, libraries, &mut findings),
+                None => error!("Can't read configuration {}.{} from {}", name, conf.name, conf.filename),
+            }
+        }
+    }
+    findings
+}
+
+// component declarations of instances which don't match the entity they are bound to by a
+// configuration specification of the architecture or by default
+pub fn check_components(libraries: &HashMap<String, Library>, lib_name: &str) -> Vec<CheckFinding> {
+    let mut names: Vec<&String> = libraries
+        .iter()
+        .filter(|(name, lib)| (lib_name.is_empty() || *name == lib_name) && lib.is_compiled())
+        .map(|(name, _)| name)
+        .collect();
+    names.sort();
+    let mut findings: Vec<CheckFinding> = Vec::new();
+    for name in names {
+        let lib = &libraries[name];
+        for design in lib.get_design_names() {
+            let mut architectures: Vec<&Architecture> = lib.designs[&design].architectures.values().collect();
+            architectures.sort_by(|a, b| a.name.cmp(&b.name));
+            for arch in architectures {
+                let unit = format!("{}({})", design, arch.name);
+                let specs = read_specifications(arch);
+                // each pair of component and entity is reported once per architecture
+                let mut compared: Vec<(&str, String)> = Vec::new();
+                for instance in arch.instances.iter().filter(|i| i.typ == "component") {
+                    // a specification of the label comes before one of all or others
+                    let of_component = || specs.iter().filter(|s| s.component == instance.component);
+                    let spec = of_component()
+                        .find(|s| s.labels.contains(&instance.label))
+                        .or_else(|| of_component().find(|s| s.labels.iter().any(|l| l == "all" || l == "others")));
+                    let bound = match spec {
+                        Some(spec) => find_specified_entity(spec, name, libraries),
+                        None => find_default_entity(arch, name, libraries, &instance.component),
+                    };
+                    let (Some(declaration), Some((bound_lib, entity))) =
+                        (find_component(arch, name, libraries, &instance.component), bound)
+                    else {
+                        continue;
+                    };
+                    let pair = (instance.component.as_str(), format!("{}.{}", bound_lib, entity.name));
+                    if compared.contains(&pair) {
+                        continue;
+                    }
+                    compared.push(pair);
+                    let mut location = CheckFinding::new(name, &unit, &arch.filename, "");
+                    location.line = find_line(&arch.filename, &format!(r"\b{}\s*:", regex::escape(&instance.label)));
+                    report_interfaces(&mut findings, &location, declaration, &format!("{}.{}", bound_lib, entity.name), entity);
+                }
+            }
+        }
+    }
+    findings
+}
+
+pub fn print_check(
+    lib_name: &String, libraries_toml_filename: &String, tool_toml_filename: &String,
+    replacements: &HashMap<String, String>, json: bool,
+) -> i32 {
+    let libraries = get_libraries(libraries_toml_filename, tool_toml_filename, replacements);
+    if !lib_name.is_empty() && !libraries.contains_key(lib_name) {
+        error!("A lib with name {} is not defined!", lib_name);
+        exit(1)
+    }
+    let mut findings = check_configurations(&libraries, lib_name);
+    findings.extend(check_components(&libraries, lib_name));
+    if json {
+        let content: Vec<serde_json::Value> = findings.iter().map(|f| f.to_json()).collect();
+        println!("{}", serde_json::to_string_pretty(&content).unwrap_or_default());
+    } else if findings.is_empty() {
+        println!("All configurations are consistent.");
+    } else {
+        for finding in &findings {
+            let location = if finding.line == 0 {
+                finding.filename.clone()
+            } else {
+                format!("{}:{}", finding.filename, finding.line)
+            };
+            println!("{}: {}.{}: {}", location, finding.library, finding.unit, finding.message);
+        }
+        println!("{} problem(s) found", findings.len());
+    }
+    i32::from(!findings.is_empty())
+}
@@ -1,14 +1,16 @@
 use std::collections::HashMap;
 
 use crate::*;
-use crate::classes::{ConfigurationInstance, Element, Instance, Library};
+use crate::classes::{BindingOverrides, Component, ConfigurationInstance, Element, Instance, Library};
 use crate::resolve_uses;
 
 pub struct Architecture {
     pub name: String,
+    pub entity: String,
     pub filename: String,
     pub uses: Vec<String>,
     pub instances: Vec<Instance>,
+    pub components: HashMap<String, Component>,
 }
 
 impl Architecture {
@@ -23,23 +25,26 @@ impl Architecture {
         &self,
         library: &String,
         libraries: &HashMap<String, Library>,
+        overrides: &BindingOverrides,
         configuration_instances: &HashMap<String, ConfigurationInstance>,
     ) -> Vec<Element> {
-        let mut ret = resolve_uses(&self.uses, library, libraries);
+        let mut deps = Vec::new();
+        let mut ret = resolve_uses(&self.uses, library, libraries, overrides, &mut deps);
         // instances of architecture
         ret.extend(resolve_instances(
             &self.instances,
             library,
             libraries,
+            overrides,
             configuration_instances,
             &self.uses,
+            &mut deps,
         ));
         // arch itself
-        ret.push(Element {
-            library: library.to_string(),
-            filename: self.filename.to_string(),
-            language: "vhdl".to_string(),
-        });
+        let unit = format!("{}({})", self.entity, self.name);
+        let mut el = Element::new(library, &self.filename, "vhdl", &unit);
+        el.depends_on = deps;
+        ret.push(el);
         ret
     }
 }
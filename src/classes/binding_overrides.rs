@@ -0,0 +1,52 @@
+// bindings given on the command line, they take precedence like a configuration
+#[derive(Clone)]
+pub struct BindingOverrides {
+    pub binds: Vec<(String, String)>, // label path from the toplevel (e.g. duv_i.sub_i) and lib.entity(arch) or lib.cfg
+    pub archs: Vec<(String, String)>, // entity or lib.entity and the architecture of its default binding
+    pub path: Vec<String>,            // labels of the instances being resolved, from the toplevel down
+}
+
+impl Default for BindingOverrides {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BindingOverrides {
+    pub fn new() -> BindingOverrides {
+        BindingOverrides {
+            binds: Vec::new(),
+            archs: Vec::new(),
+            path: Vec::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.binds.is_empty() && self.archs.is_empty()
+    }
+
+    // overrides for the units below the instance with label
+    pub fn enter(&self, label: &str) -> BindingOverrides {
+        let mut ret = self.clone();
+        ret.path.push(label.to_string());
+        ret
+    }
+
+    // target of the instance with label in the current path
+    pub fn get_bind(&self, label: &str) -> Option<String> {
+        let mut path = self.path.clone();
+        path.push(label.to_string());
+        let path = path.join(".");
+        self.binds
+            .iter()
+            .find(|(p, _)| p.eq_ignore_ascii_case(&path))
+            .map(|(_, target)| target.clone())
+    }
+
+    // architecture for the default binding of entity in library, lib.entity before entity
+    pub fn get_arch(&self, library: &str, entity: &str) -> Option<String> {
+        let qualified = format!("{}.{}", library, entity);
+        let find = |name: &str| self.archs.iter().find(|(e, _)| e.eq_ignore_ascii_case(name)).map(|(_, arch)| arch.clone());
+        find(&qualified).or_else(|| find(entity))
+    }
+}
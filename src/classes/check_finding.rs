@@ -0,0 +1,29 @@
+pub struct CheckFinding {
+    pub library: String,
+    pub unit: String, // configuration or architecture
+    pub filename: String,
+    pub line: usize, // 0 if unknown
+    pub message: String,
+}
+
+impl CheckFinding {
+    pub fn new(library: &str, unit: &str, filename: &str, message: &str) -> CheckFinding {
+        CheckFinding {
+            library: library.to_string(),
+            unit: unit.to_string(),
+            filename: filename.to_string(),
+            line: 0,
+            message: message.to_string(),
+        }
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "library": self.library,
+            "unit": self.unit,
+            "filename": self.filename,
+            "line": self.line,
+            "message": self.message,
+        })
+    }
+}
@@ -0,0 +1,23 @@
+use crate::classes::InterfaceElement;
+
+pub struct Component {
+    pub name: String,
+    pub generics: Vec<InterfaceElement>,
+    pub ports: Vec<InterfaceElement>,
+}
+
+impl Default for Component {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Component {
+    pub fn new() -> Component {
+        Component {
+            name: String::new(),
+            generics: Vec::new(),
+            ports: Vec::new(),
+        }
+    }
+}
@@ -5,7 +5,7 @@ use std::process::exit;
 use log::error;
 
 use crate::*;
-use crate::classes::{ConfigurationInstance, Element, Library};
+use crate::classes::{BindingOverrides, ConfigurationInstance, Element, Library};
 
 pub struct Configuration {
     pub library: String,
@@ -44,8 +44,9 @@ impl Configuration {
         self.uses.extend(uses.clone());
     }
     */
-    pub fn resolve(&self, libraries: &HashMap<String, Library>) -> Vec<Element> {
-        let mut ret = resolve_uses(&self.uses, &self.library, libraries);
+    pub fn resolve(&self, libraries: &HashMap<String, Library>, overrides: &BindingOverrides) -> Vec<Element> {
+        let mut deps = Vec::new();
+        let mut ret = resolve_uses(&self.uses, &self.library, libraries, overrides, &mut deps);
         let lib = match libraries.get(&self.library) {
             None => {
                 error!("library '{}' is unknown", &self.library);
@@ -61,19 +62,20 @@ impl Configuration {
                 );
             }
             Some(des) => {
-                ret.extend(des.resolve(
+                let resolved = des.resolve(
                     &self.library,
                     libraries,
+                    overrides,
                     self.architecture.clone(),
                     &self.instances,
-                ));
+                );
+                add_dependency(&mut deps, &resolved, "architecture", "");
+                ret.extend(resolved);
             }
         }
-        ret.push(Element {
-            library: self.library.clone(),
-            filename: self.filename.clone(),
-            language: "vhdl".to_string(),
-        });
+        let mut el = Element::new(&self.library, &self.filename, "vhdl", &self.name);
+        el.depends_on = deps;
+        ret.push(el);
         ret
     }
 }
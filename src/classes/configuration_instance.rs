@@ -3,7 +3,7 @@ use std::fmt;
 
 use log::error;
 
-use crate::classes::{Element, Library};
+use crate::classes::{BindingOverrides, Element, Library};
 
 pub struct ConfigurationInstance {
     pub library: String,
@@ -15,7 +15,7 @@ pub struct ConfigurationInstance {
 }
 
 impl ConfigurationInstance {
-    pub fn resolve(&self, library: &String, libraries: &HashMap<String, Library>) -> Vec<Element> {
+    pub fn resolve(&self, library: &String, libraries: &HashMap<String, Library>, overrides: &BindingOverrides) -> Vec<Element> {
         let lib_name = if self.library == "work" {
             library
         } else {
@@ -26,7 +26,7 @@ impl ConfigurationInstance {
                 error!("Library '{}' is unknown", lib_name)
             }
             Some(l) => {
-                return l.resolve(&self.component, libraries);
+                return l.resolve(&self.component, libraries, overrides);
             }
         }
         Vec::new()
@@ -0,0 +1,21 @@
+pub struct Dependency {
+    pub library: String,
+    pub unit: String,
+    pub filename: String,
+    pub kind: String, // [use, entity, architecture, instance, package]
+    pub label: String, // instance label or the use clause
+    pub binding: String, // instances only: [configuration, entity, default, verilog, external, unresolved, override]
+}
+
+impl Dependency {
+    pub fn copy(&self) -> Dependency {
+        Dependency {
+            library: self.library.clone(),
+            unit: self.unit.clone(),
+            filename: self.filename.clone(),
+            kind: self.kind.clone(),
+            label: self.label.clone(),
+            binding: self.binding.clone(),
+        }
+    }
+}
@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use log::{error, warn};
 
-use crate::classes::{Architecture, ConfigurationInstance, Element, Entity, Library};
+use crate::classes::{Architecture, BindingOverrides, ConfigurationInstance, Element, Entity, Library};
 
 //use std::fmt;
 
@@ -44,10 +44,12 @@ impl Design {
         &self,
         library: &String,
         libraries: &HashMap<String, Library>,
+        overrides: &BindingOverrides,
         arch: String,
         configuration_instances: &HashMap<String, ConfigurationInstance>,
     ) -> Vec<Element> {
-        let mut ret = self.entity.resolve(library, libraries);
+        let mut ret = self.entity.resolve(library, libraries, overrides);
+        let entity_dep = ret.last().map(|el| el.as_dependency("entity", ""));
         match self.architectures.get(&arch) {
             None => {
                 error!(
@@ -61,7 +63,11 @@ impl Design {
                     // remove entity from list before inserting resolved architecture
                     ret.pop();
                 }
-                ret.extend(a.resolve(library, libraries, configuration_instances))
+                let mut resolved = a.resolve(library, libraries, overrides, configuration_instances);
+                if let (Some(el), Some(dep)) = (resolved.last_mut(), entity_dep) {
+                    el.depends_on.push(dep);
+                }
+                ret.extend(resolved)
             }
         }
         ret
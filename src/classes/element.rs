@@ -1,15 +1,43 @@
+use crate::classes::Dependency;
+
 pub struct Element {
     pub library: String,
     pub filename: String,
     pub language: String,
+    pub unit: String,
+    pub depends_on: Vec<Dependency>,
 }
 
 impl Element {
+    pub fn new(library: &str, filename: &str, language: &str, unit: &str) -> Element {
+        Element {
+            library: library.to_string(),
+            filename: filename.to_string(),
+            language: language.to_string(),
+            unit: unit.to_string(),
+            depends_on: Vec::new(),
+        }
+    }
+
     pub fn copy(&self) -> Element {
         Element {
             library: self.library.clone(),
             filename: self.filename.clone(),
             language: self.language.clone(),
+            unit: self.unit.clone(),
+            depends_on: self.depends_on.iter().map(|d| d.copy()).collect(),
+        }
+    }
+
+    // dependency on this element, used by the element which needs it
+    pub fn as_dependency(&self, kind: &str, label: &str) -> Dependency {
+        Dependency {
+            library: self.library.clone(),
+            unit: self.unit.clone(),
+            filename: self.filename.clone(),
+            kind: kind.to_string(),
+            label: label.to_string(),
+            binding: String::new(),
         }
     }
 }
@@ -24,4 +52,4 @@ impl fmt::Display for Element {
         )
     }
 }
-*/
\ No newline at end of file
+*/
@@ -1,12 +1,15 @@
 use std::collections::HashMap;
 
 use crate::*;
-use crate::classes::{Element, Library};
+use crate::classes::{BindingOverrides, Element, InterfaceElement, Library};
 
 pub struct Entity {
     pub name: String,
     pub filename: String,
     pub uses: Vec<String>,
+    pub has_ports: bool,
+    pub generics: Vec<InterfaceElement>,
+    pub ports: Vec<InterfaceElement>,
 }
 
 impl Entity {
@@ -17,13 +20,12 @@ impl Entity {
     pub fn extend_uses(&mut self, uses: Vec<String>) {
         self.uses.extend(uses.clone());
     }*/
-    pub fn resolve(&self, library: &String, libraries: &HashMap<String, Library>) -> Vec<Element> {
-        let mut ret = resolve_uses(&self.uses, library, libraries);
-        ret.push(Element {
-            library: library.to_string(),
-            filename: self.filename.to_string(),
-            language: "vhdl".to_string(),
-        });
+    pub fn resolve(&self, library: &String, libraries: &HashMap<String, Library>, overrides: &BindingOverrides) -> Vec<Element> {
+        let mut deps = Vec::new();
+        let mut ret = resolve_uses(&self.uses, library, libraries, overrides, &mut deps);
+        let mut el = Element::new(library, &self.filename, "vhdl", &self.name);
+        el.depends_on = deps;
+        ret.push(el);
         ret
     }
 }
@@ -0,0 +1,46 @@
+pub struct HierarchyNode {
+    pub library: String,
+    pub unit: String,
+    pub filename: String,
+    pub kind: String, // [toplevel, use, entity, architecture, instance, package]
+    pub label: String,
+    pub language: String, // [vhdl, verilog, external], empty if unresolved
+    pub binding: String, // instances only: [configuration, entity, default, verilog, external, unresolved, override]
+    pub children: Vec<HierarchyNode>,
+}
+
+impl HierarchyNode {
+    pub fn new(library: &str, unit: &str, filename: &str, kind: &str, label: &str) -> HierarchyNode {
+        HierarchyNode {
+            library: library.to_string(),
+            unit: unit.to_string(),
+            filename: filename.to_string(),
+            kind: kind.to_string(),
+            label: label.to_string(),
+            language: String::new(),
+            binding: String::new(),
+            children: Vec::new(),
+        }
+    }
+
+    // same node without its children
+    pub fn copy_node(&self) -> HierarchyNode {
+        let mut node = HierarchyNode::new(&self.library, &self.unit, &self.filename, &self.kind, &self.label);
+        node.language = self.language.clone();
+        node.binding = self.binding.clone();
+        node
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "library": self.library,
+            "unit": self.unit,
+            "filename": self.filename,
+            "kind": self.kind,
+            "label": self.label,
+            "language": self.language,
+            "binding": self.binding,
+            "children": self.children.iter().map(|c| c.to_json()).collect::<Vec<_>>(),
+        })
+    }
+}
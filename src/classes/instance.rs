@@ -5,7 +5,10 @@ use std::process::exit;
 use log::{error, info, trace, warn};
 use regex::Regex;
 
-use crate::classes::{ConfigurationInstance, Element, Library, RE_ENT, RE_ENT2};
+use crate::add_instance_dependency;
+use crate::classes::{
+    BindingOverrides, ConfigurationInstance, Dependency, Element, Library, RE_ENT, RE_ENT2,
+};
 
 pub struct Instance {
     pub label: String,
@@ -15,12 +18,70 @@ pub struct Instance {
 }
 
 impl Instance {
+    // dependency of an instance which couldn't be bound
+    fn unresolved(&self, library: &str) -> Dependency {
+        Dependency {
+            library: library.to_string(),
+            unit: self.component.clone(),
+            filename: String::new(),
+            kind: String::from("instance"),
+            label: self.label.clone(),
+            binding: String::from("unresolved"),
+        }
+    }
+
     pub fn resolve(
         &self,
         library: &String,
         libraries: &HashMap<String, Library>,
+        overrides: &BindingOverrides,
         configuration_instances: &HashMap<String, ConfigurationInstance>,
         uses: &Vec<String>,
+        deps: &mut Vec<Dependency>,
+    ) -> Vec<Element> {
+        // a binding of the command line takes precedence like a configuration
+        let target = overrides.get_bind(&self.label);
+        let overrides = overrides.enter(&self.label);
+        match target {
+            Some(target) => self.resolve_override(&target, library, libraries, &overrides, deps),
+            None => self.resolve_binding(library, libraries, &overrides, configuration_instances, uses, deps),
+        }
+    }
+
+    fn resolve_override(
+        &self,
+        target: &str,
+        library: &str,
+        libraries: &HashMap<String, Library>,
+        overrides: &BindingOverrides,
+        deps: &mut Vec<Dependency>,
+    ) -> Vec<Element> {
+        let lib = match RE_ENT.captures(target).and_then(|caps| caps.name("lib").map(|m| m.as_str().to_string())) {
+            Some(lib) if lib != "work" => lib,
+            _ => library.to_string(),
+        };
+        let resolved = match libraries.get(&lib) {
+            None => {
+                error!("Library '{}' of binding {} is unknown", lib, target);
+                Vec::new()
+            }
+            Some(l) => l.resolve(&target.to_string(), libraries, overrides),
+        };
+        if resolved.is_empty() {
+            deps.push(self.unresolved(&lib));
+        }
+        add_instance_dependency(deps, &resolved, &self.label, "override");
+        resolved
+    }
+
+    fn resolve_binding(
+        &self,
+        library: &String,
+        libraries: &HashMap<String, Library>,
+        overrides: &BindingOverrides,
+        configuration_instances: &HashMap<String, ConfigurationInstance>,
+        uses: &Vec<String>,
+        deps: &mut Vec<Dependency>,
     ) -> Vec<Element> {
         let re_use_2 = match Regex::new(r"(?imsx)^(?P<lib>\w+)\.(?P<part>\w+)$") {
             Ok(r) => r,
@@ -37,12 +98,16 @@ impl Instance {
         // in configuration_instances?
         if let Some(ci) = configuration_instances.get(&self.label) {
             trace!("    in configuration_instances");
-            return ci.resolve(library, libraries);
+            let resolved = ci.resolve(library, libraries, overrides);
+            add_instance_dependency(deps, &resolved, &self.label, "configuration");
+            return resolved;
         }
         let all = "all@".to_owned() + &*self.component;
         if let Some(ci) = configuration_instances.get(&all) {
             trace!("    in configuration_instances");
-            return ci.resolve(library, libraries);
+            let resolved = ci.resolve(library, libraries, overrides);
+            add_instance_dependency(deps, &resolved, &self.label, "configuration");
+            return resolved;
         }
 
         let mut ret = Vec::new();
@@ -54,21 +119,33 @@ impl Instance {
             // if component.contains("otp")
             match libraries.get(lib) {
                 None => {
-                    error!("Library '{}' is unknown", lib)
+                    error!("Library '{}' is unknown", lib);
+                    deps.push(self.unresolved(lib));
                 }
                 Some(l) => {
                     trace!("    entity");
-                    ret.extend(l.resolve(&self.component, libraries))
+                    let resolved = l.resolve(&self.component, libraries, overrides);
+                    if resolved.is_empty() && !l.ignore {
+                        deps.push(self.unresolved(lib));
+                    }
+                    add_instance_dependency(deps, &resolved, &self.label, "entity");
+                    ret.extend(resolved)
                 }
             }
         } else if self.typ == "configuration" {
             match libraries.get(lib) {
                 None => {
-                    error!("Library '{}' is unknown", lib)
+                    error!("Library '{}' is unknown", lib);
+                    deps.push(self.unresolved(lib));
                 }
                 Some(l) => {
                     trace!("    configuration");
-                    ret.extend(l.resolve(&self.component, libraries))
+                    let resolved = l.resolve(&self.component, libraries, overrides);
+                    if resolved.is_empty() && !l.ignore {
+                        deps.push(self.unresolved(lib));
+                    }
+                    add_instance_dependency(deps, &resolved, &self.label, "configuration");
+                    ret.extend(resolved)
                 }
             }
         } else {
@@ -104,7 +181,9 @@ impl Instance {
                                             || l.configurations.contains_key(e)
                                             || l.modules.contains_key(e)
                                         {
-                                            ret.extend(l.resolve(&self.component, libraries));
+                                            let resolved = l.resolve(&self.component, libraries, overrides);
+                                            add_instance_dependency(deps, &resolved, &self.label, "configuration");
+                                            ret.extend(resolved);
                                         }
                                     }
                                 }
@@ -134,6 +213,21 @@ impl Instance {
                 for lib_name in lib_names {
                     if let Some(lib_tmp) = libraries.get(&*lib_name) {
                         if let Some(design) = lib_tmp.designs.get(&self.component) {
+                            let mut arch = overrides.get_arch(&lib_name, &self.component);
+                            if let Some(a) = arch.as_ref().filter(|a| !design.architectures.contains_key(*a)) {
+                                warn!(
+                                    "Design {} has no architecture {}, the override is ignored",
+                                    self.component, a
+                                );
+                                arch = None;
+                            }
+                            if let Some(arch) = arch {
+                                found = true;
+                                let resolved = lib_tmp.resolve(&format!("{}({})", self.component, arch), libraries, overrides);
+                                add_instance_dependency(deps, &resolved, &self.label, "override");
+                                ret.extend(resolved);
+                                break;
+                            }
                             match design.architectures.len() {
                                 0 => {
                                     warn!("Couldn't resolve component instance {}, because design {} has no architectures!",self.label,self.component);
@@ -145,7 +239,9 @@ impl Instance {
                                         design_name.push('(');
                                         design_name.push_str(&a.name);
                                         design_name.push(')');
-                                        ret.extend(lib_tmp.resolve(&design_name, libraries));
+                                        let resolved = lib_tmp.resolve(&design_name, libraries, overrides);
+                                        add_instance_dependency(deps, &resolved, &self.label, "default");
+                                        ret.extend(resolved);
                                     }
                                     break;
                                 }
@@ -178,13 +274,29 @@ impl Instance {
                             "{} can be resolved to a Verilog module in library {}.",
                             &self.component, name
                         );
-                        ret.extend(lib.resolve(&self.component, libraries));
+                        let resolved = lib.resolve(&self.component, libraries, overrides);
+                        add_instance_dependency(deps, &resolved, &self.label, "verilog");
+                        ret.extend(resolved);
+                        found = true;
+                    }
+                }
+            }
+            if !found {
+                // component of a precompiled or vendor library
+                for usage in uses {
+                    let lib_name = usage.split('.').next().unwrap_or("");
+                    if let Some(lib) = libraries.get(lib_name).filter(|l| l.is_external()) {
+                        let resolved = lib.resolve(&self.component, libraries, overrides);
+                        add_instance_dependency(deps, &resolved, &self.label, "external");
+                        ret.extend(resolved);
                         found = true;
+                        break;
                     }
                 }
             }
             if !found {
                 error!("Can't resolve {} in lib {}", self.to_string(), library);
+                deps.push(self.unresolved(library));
             }
         }
         ret
@@ -0,0 +1,26 @@
+// generic or port of an entity or component
+#[derive(Clone)]
+pub struct InterfaceElement {
+    pub name: String,
+    pub mode: String, // [in, out, inout, buffer, linkage], empty for generics
+    pub typ: String,
+    pub default: String, // empty without default value
+}
+
+impl InterfaceElement {
+    pub fn new(name: &str, mode: &str, typ: &str, default: &str) -> InterfaceElement {
+        InterfaceElement {
+            name: name.to_string(),
+            mode: mode.to_string(),
+            typ: typ.to_string(),
+            default: default.to_string(),
+        }
+    }
+
+    // type without constraint and selected name prefix, e.g. unsigned for ieee.numeric_std.unsigned(7 downto 0)
+    pub fn type_mark(&self) -> &str {
+        let typ = self.typ.split('(').next().unwrap_or("");
+        let typ = typ.split(" range ").next().unwrap_or("").trim();
+        typ.rsplit('.').next().unwrap_or(typ)
+    }
+}
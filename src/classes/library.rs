@@ -11,6 +11,7 @@ use crate::classes::Architecture;
 use crate::classes::Configuration;
 use crate::classes::Design;
 use crate::classes::Entity;
+use crate::classes::InterfaceElement;
 use crate::classes::Package;
 use crate::classes::RE_CONF_COMP_SPEC;
 use crate::classes::RE_CONFIGURATION;
@@ -21,7 +22,9 @@ use crate::classes::RE_LIBS;
 use crate::classes::RE_MODULE;
 use crate::classes::RE_PACKAGE;
 use crate::classes::RE_PACKAGE_BODY;
+use crate::classes::RE_PORT;
 use crate::classes::RE_USE;
+use crate::classes::BindingOverrides;
 
 pub struct Library {
     pub name: String,
@@ -31,6 +34,8 @@ pub struct Library {
     pub modules: HashMap<String, String>,
     pub depends_on_libs: Vec<String>,
     pub ignore: bool,
+    pub external: bool,
+    pub precompiled: String,
     pub vhdl_scope: Vec<String>,
     pub verilog_scope: Vec<String>,
     pub all_vhdl_elements: Vec<Element>,
@@ -53,6 +58,8 @@ impl Library {
             modules: HashMap::new(),
             depends_on_libs: Vec::new(),
             ignore: false,
+            external: false,
+            precompiled: String::new(),
             vhdl_scope: Vec::new(),
             verilog_scope: Vec::new(),
             all_vhdl_elements: Vec::new(),
@@ -69,6 +76,16 @@ impl Library {
         println!("---------------------------------------------------");
     }
 
+    // precompiled or vendor library without sources
+    pub fn is_external(&self) -> bool {
+        self.external || !self.precompiled.is_empty()
+    }
+
+    // library with sources to compile
+    pub fn is_compiled(&self) -> bool {
+        !self.ignore && !self.is_external()
+    }
+
     pub fn has_design(&self, name: &String) -> bool {
         return self.designs.get(name).is_some();
     }
@@ -83,6 +100,9 @@ impl Library {
                         name: String::from(""),
                         filename: String::from(""),
                         uses: Vec::new(),
+                        has_ports: false,
+                        generics: Vec::new(),
+                        ports: Vec::new(),
                     },
                     architectures: HashMap::new(),
                 };
@@ -124,6 +144,7 @@ impl Library {
                     header: String::from(""),
                     body: String::from(""),
                     uses: Vec::new(),
+                    components: HashMap::new(),
                 };
                 self.packages.insert(name.to_string(), p);
             }
@@ -135,7 +156,7 @@ impl Library {
     }
 
     pub fn analyze(&mut self) {
-        if self.ignore {
+        if !self.is_compiled() {
             return;
         }
         info!("Analyzing library {} ....", self.name);
@@ -148,11 +169,7 @@ impl Library {
                         if let Some(filename) = path.to_str() {
                             if !used_filenames.contains(filename) {
                                 used_filenames.insert(filename.to_string());
-                                self.all_vhdl_elements.push(Element {
-                                    library: self.name.clone(),
-                                    filename: filename.to_string(),
-                                    language: String::from("vhdl"),
-                                });
+                                self.all_vhdl_elements.push(Element::new(&self.name, filename, "vhdl", ""));
                                 self.analyze_vhdl_file(filename)
                             } else {
                                 trace!("Ignoring duplicate glob entry {}", filename);
@@ -173,11 +190,7 @@ impl Library {
                         if let Some(filename) = path.to_str() {
                             if !used_filenames.contains(filename) {
                                 used_filenames.insert(filename.to_string());
-                                self.all_verilog_elements.push(Element {
-                                    library: self.name.clone(),
-                                    filename: filename.to_string(),
-                                    language: String::from("vhdl"),
-                                });
+                                self.all_verilog_elements.push(Element::new(&self.name, filename, "verilog", ""));
                                 self.analyze_verilog_file(filename)
                             } else {
                                 trace!("Ignoring duplicate glob entry {}", filename);
@@ -210,6 +223,19 @@ impl Library {
         info!("Analyze {}", filename);
         // ToDo: Use RegexSet?
         let mut content = self.read_file(filename);
+        // ports, generics and component declarations are stripped by pre_work_file_content
+        let raw = RE_COMMENT.replace_all(&content, "").to_ascii_lowercase();
+        let mut with_ports: Vec<String> = Vec::new();
+        let mut interfaces: HashMap<String, (Vec<InterfaceElement>, Vec<InterfaceElement>)> = HashMap::new();
+        for cap in RE_ENTITY.captures_iter(&raw) {
+            if RE_PORT.is_match(&cap["content"]) {
+                with_ports.push(cap["name"].to_string());
+            }
+            let generics = get_interface_elements(&cap["content"], "generic");
+            let ports = get_interface_elements(&cap["content"], "port");
+            interfaces.entry(cap["name"].to_string()).or_insert((generics, ports));
+        }
+        let mut components = get_components(&raw);
         // and remove unnecessary stuff
         content = pre_work_file_content(&content);
         // Uses, to add to all other found ones
@@ -243,10 +269,14 @@ impl Library {
         for cap in RE_ENTITY.captures_iter(&content) {
             let design_name = &cap["name"];
             let d: &mut Design = self.get_design(&design_name.to_string());
+            let (generics, ports) = interfaces.remove(design_name).unwrap_or_default();
             let e: Entity = Entity {
                 name: design_name.to_string(),
                 filename: filename.to_string(),
                 uses: uses.clone(),
+                has_ports: with_ports.contains(&design_name.to_string()),
+                generics,
+                ports,
             };
             d.set_entity(e);
             trace!("Found entity {}", design_name.to_string());
@@ -257,9 +287,11 @@ impl Library {
             let d: &mut Design = self.get_design(&design_name.to_string());
             let mut arch = Architecture {
                 name: name.to_string(),
+                entity: design_name.to_string(),
                 filename: filename.to_string(),
                 uses: uses.clone(), //.to_owned(),
                 instances: Vec::new(),
+                components: components.remove(&format!("{}({})", design_name, name)).unwrap_or_default(),
             };
             trace!(
                 "Found architecture {} of {}",
@@ -377,12 +409,14 @@ impl Library {
                 pkg = self.get_package(pkg_name);
                 pkg.set_header(&filename.to_string());
                 pkg.extend_uses(uses.clone());
+                pkg.components = components.remove(pkg_name).unwrap_or_default();
             } else {
                 let p = Package {
                     name: pkg_name.clone(),
                     header: filename.to_string(),
                     body: "".to_string(),
                     uses: uses.clone(),
+                    components: components.remove(pkg_name).unwrap_or_default(),
                 };
                 self.packages.insert(pkg_name.clone(), p);
             }
@@ -400,6 +434,7 @@ impl Library {
                     header: "".to_string(),
                     body: filename.to_string(),
                     uses: uses.clone(),
+                    components: HashMap::new(),
                 };
                 self.packages.insert(pkg_name.clone(), p);
             }
@@ -427,10 +462,14 @@ impl Library {
         ret
     }
 
-    pub fn resolve(&self, name: &String, libraries: &HashMap<String, Library>) -> Vec<Element> {
+    pub fn resolve(&self, name: &String, libraries: &HashMap<String, Library>, overrides: &BindingOverrides) -> Vec<Element> {
         if self.ignore {
             return Vec::new();
         }
+        // satisfied leaf, nothing to compile
+        if self.is_external() {
+            return Vec::from([Element::new(&self.name, "", "external", name)]);
+        }
         let Some(caps) = RE_ENT.captures(name.as_str()) else {
             error!("No entity or configuration found in {}!", name);
             return Vec::new();
@@ -439,13 +478,19 @@ impl Library {
         let arch = caps.name("arch").map_or("", |m| m.as_str()).to_string();
         if arch.is_empty() {
             if let Some(conf) = self.configurations.get(&entity) {
-                return conf.resolve(libraries);
+                return conf.resolve(libraries, overrides);
             }
             if let Some(pack) = self.packages.get(&entity) {
-                return pack.resolve(&self.name, libraries);
+                return pack.resolve(&self.name, libraries, overrides);
             }
             // test if there's a design with just one architecture!
             if let Some(design) = self.designs.get(&entity) {
+                if let Some(arch) = overrides.get_arch(&self.name, &entity) {
+                    if design.architectures.contains_key(&arch) {
+                        return self.resolve(&format!("{}({})", entity, arch), libraries, overrides);
+                    }
+                    warn!("Design {} has no architecture {}, the override is ignored", entity, arch);
+                }
                 match design.architectures.len() {
                     0 => {
                         warn!(
@@ -459,7 +504,7 @@ impl Library {
                             design_name.push('(');
                             design_name.push_str(&a.name);
                             design_name.push(')');
-                            return self.resolve(&design_name, libraries);
+                            return self.resolve(&design_name, libraries, overrides);
                         }
                     }
                     _ => {
@@ -477,11 +522,7 @@ impl Library {
             };
             if let Some(filename) = self.modules.get(&*entity) {
                 info!("{} can be resolved to Verilog module!", entity,);
-                let el = Element {
-                    library: self.name.clone(),
-                    filename: filename.clone(),
-                    language: "verilog".to_string(),
-                };
+                let el = Element::new(&self.name, filename, "verilog", &entity);
                 return Vec::from([el]);
             }
             error!(
@@ -505,11 +546,22 @@ impl Library {
                     );
                     Vec::new()
                 }
-                Some(des) => des.resolve(&self.name, libraries, arch, &Default::default()),
+                Some(des) => des.resolve(&self.name, libraries, overrides, arch, &Default::default()),
             }
         }
     }
 
+    // configurations and the architectures of entities without ports, the usual testbenches
+    pub fn list_toplevel_candidates(&self) -> Vec<String> {
+        self.list_designs()
+            .into_iter()
+            .filter(|name| match name.split_once('(') {
+                Some((entity, _)) => self.designs.get(entity).is_some_and(|d| !d.entity.has_ports),
+                None => true,
+            })
+            .collect()
+    }
+
     pub fn list_designs(&self) -> Vec<String> {
         if self.ignore {
             return Vec::new();
@@ -0,0 +1,15 @@
+pub struct LibraryFiles {
+    pub library: String,
+    pub language: String, // [vhdl, verilog]
+    pub files: Vec<String>,
+}
+
+impl LibraryFiles {
+    pub fn new(library: &str, language: &str) -> LibraryFiles {
+        LibraryFiles {
+            library: library.to_string(),
+            language: language.to_string(),
+            files: Vec::new(),
+        }
+    }
+}
@@ -1,26 +1,58 @@
 pub use architecture::Architecture;
+pub use binding_overrides::*;
+pub use check_finding::CheckFinding;
+pub use component::Component;
 pub use configuration::Configuration;
 pub use configuration_instance::ConfigurationInstance;
+pub use dependency::Dependency;
 pub use design::Design;
 pub use element::*;
 pub use entity::Entity;
+pub use hierarchy_node::HierarchyNode;
 pub use instance::Instance;
+pub use interface_element::InterfaceElement;
 pub use library::Library;
+pub use library_files::LibraryFiles;
 pub use package::Package;
 pub use re_definitions::*;
+pub use root_unit::RootUnit;
+pub use script_options::ScriptOptions;
+pub use step_result::StepResult;
+pub use test_options::TestOptions;
+pub use test_result::TestResult;
+pub use tool_call::ToolCall;
 pub use tool_lang_config::*;
+pub use tool_stage_config::ToolStageConfig;
+pub use tool_test_config::ToolTestConfig;
+pub use tree_options::TreeOptions;
 
 pub(crate) mod architecture;
 
+mod binding_overrides;
+mod check_finding;
+mod component;
 mod configuration;
 mod configuration_instance;
+mod dependency;
 mod design;
 mod element;
 mod entity;
+mod hierarchy_node;
 mod instance;
+mod interface_element;
 mod library;
+mod library_files;
 mod package;
 mod re_definitions;
+mod root_unit;
+mod script_options;
+mod step_result;
+mod test_options;
+mod test_result;
+mod tool_call;
 pub mod tool_config;
 mod tool_lang_config;
+mod tool_stage_config;
+mod tool_test_config;
+mod tree_options;
 
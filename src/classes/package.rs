@@ -3,13 +3,14 @@ use std::collections::HashMap;
 use log::warn;
 
 use crate::*;
-use crate::classes::{Element, Library};
+use crate::classes::{BindingOverrides, Component, Element, Library};
 
 pub struct Package {
     pub name: String,
     pub header: String,
     pub body: String,
     pub uses: Vec<String>,
+    pub components: HashMap<String, Component>,
 }
 
 impl Package {
@@ -45,20 +46,19 @@ impl Package {
     pub fn extend_uses(&mut self, uses: Vec<String>) {
         self.uses.extend(uses.clone());
     }
-    pub fn resolve(&self, library: &String, libraries: &HashMap<String, Library>) -> Vec<Element> {
-        let mut ret = resolve_uses(&self.uses, library, libraries);
+    pub fn resolve(&self, library: &String, libraries: &HashMap<String, Library>, overrides: &BindingOverrides) -> Vec<Element> {
+        let mut deps = Vec::new();
+        let mut ret = resolve_uses(&self.uses, library, libraries, overrides, &mut deps);
         // package itself
-        ret.push(Element {
-            library: library.clone(),
-            filename: self.header.clone(),
-            language: "vhdl".to_string(),
-        });
+        let mut header = Element::new(library, &self.header, "vhdl", &self.name);
+        header.depends_on = deps;
         if !self.body.is_empty() && self.body != self.header {
-            ret.push(Element {
-                library: library.clone(),
-                filename: self.body.clone(),
-                language: "vhdl".to_string(),
-            });
+            let mut body = Element::new(library, &self.body, "vhdl", &format!("{} body", self.name));
+            body.depends_on.push(header.as_dependency("package", ""));
+            ret.push(header);
+            ret.push(body);
+        } else {
+            ret.push(header);
         }
         ret
     }
@@ -25,6 +25,24 @@ pub static RE_ENTITY: Lazy<Regex> = Lazy::new(|| {
             (?P<end>\bend(\s*(;|(\s+[^;]*?;))))",
     ).unwrap()
 });
+pub static RE_PORT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\bport\s*\(").unwrap()
+});
+pub static RE_INTERFACE_LIST: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(?P<kind>generic|port)\s*\(").unwrap()
+});
+pub static RE_COMPONENT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?imsx)
+            \bcomponent\s+(?P<name>\w+)\s+(is\s+)?
+                (?P<content>(generic|port)\s*\(.*?)
+            \bend\s+component\b",
+    ).unwrap()
+});
+// architectures and package declarations, the units component declarations belong to
+pub static RE_COMPONENT_OWNER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?is)\b(architecture\s+(?P<arch>\w+)\s+of\s+(?P<entity>\w+)|package\s+(?P<package>\w+))\s+is\b").unwrap()
+});
 pub static RE_ARCHITECTURE: Lazy<Regex> = Lazy::new(|| {
     Regex::new(
         r"(?imsx)
@@ -67,7 +85,7 @@ pub static RE_FUNC_PROC: Lazy<Regex> = Lazy::new(|| {
     ).unwrap()
 });
 pub static RE_GENERATE: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"(?imx)^.*\bgenerate\b.*?$").unwrap()
+    Regex::new(r"(?imx)^.*\b(generate|block)\b.*?$").unwrap()
 });
 pub static RE_INSTANCE: Lazy<Regex> = Lazy::new(|| {
     Regex::new(
@@ -100,8 +118,7 @@ pub static RE_CONF_COMP_SPEC: Lazy<Regex> = Lazy::new(|| {
     Regex::new(
         r"(?imsx)
         \bfor\s+(?P<label>\w+(\s*,\s*\w+)*)\s*:\s*(?P<comp>\w+)\s+
-            use\s+((?P<open>open)|(configuration\s+(?P<conf>\S+?))|(entity\s+(?P<entity>\S+?)))\s*;\s*
-        \bend\s+for\s*;",
+            use\s+((?P<open>open)|(configuration\s+(?P<conf>\S+?))|(entity\s+(?P<entity>\S+?)))\s*;",
     ).unwrap()
 });
 pub static RE_ENT: Lazy<Regex> = Lazy::new(|| {
@@ -130,3 +147,34 @@ pub static RE_MODULE_INST: Lazy<Regex> = Lazy::new(|| {
 pub static RE_ENVVAR: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"\$((?P<var1>\w+)|(\{(?P<var2>[^}]+?)}))").unwrap()
 });
+// headers of architectures and configurations, the names are compared by the caller
+pub static RE_ARCHITECTURE_HEADER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?is)\barchitecture\s+(?P<name>\w+)\s+of\s+(?P<entity>\w+)\s+is\b").unwrap()
+});
+pub static RE_NEXT_ARCHITECTURE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?is)\barchitecture\s+\w+\s+of\b").unwrap()
+});
+pub static RE_CONFIGURATION_HEADER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?is)\bconfiguration\s+(?P<name>\w+)\s+of\s+(?P<entity>\w+)\s+is\b").unwrap()
+});
+// component and block configurations and their ends, in the order they are written
+pub static RE_CONF_TOKEN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?isx)
+        \bfor\s+(?P<labels>\w+(\s*,\s*\w+)*)\s*:\s*(?P<comp>\w+)
+            (\s+use\s+((?P<open>open)|(entity\s+(?P<entity>[\w.]+(\s*\(\s*\w+\s*\))?))|(configuration\s+(?P<conf>[\w.]+)))[^;]*;)?
+        |\bfor\s+(?P<block>\w+)
+        |\bend\s+for\s*;",
+    ).unwrap()
+});
+// labelled generate and block statements and their ends, in the architecture body
+pub static RE_GENERATE_LABEL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?is)\b(?P<label>\w+)\s*:\s*((for|if|case)\b[^;]*?\bgenerate|block)\b").unwrap()
+});
+pub static RE_END_GENERATE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?is)\bend\s+(generate|block)\b").unwrap()
+});
+// configuration specification in an architecture, only the labels and the component
+pub static RE_CONF_SPEC: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?is)\bfor\s+(?P<labels>\w+(\s*,\s*\w+)*)\s*:\s*(?P<comp>\w+)\s+use\b").unwrap()
+});
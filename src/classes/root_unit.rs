@@ -0,0 +1,32 @@
+pub struct RootUnit {
+    pub library: String,
+    pub unit: String,
+    pub kind: String, // [architecture, configuration]
+    pub entity: String,
+    pub testbench: bool, // entity without ports or named tb_* or *_tb
+    pub preferred: bool, // configuration of a root entity, or root architecture without such a configuration
+}
+
+impl RootUnit {
+    pub fn new(library: &str, unit: &str, kind: &str, entity: &str) -> RootUnit {
+        RootUnit {
+            library: library.to_string(),
+            unit: unit.to_string(),
+            kind: kind.to_string(),
+            entity: entity.to_string(),
+            testbench: false,
+            preferred: true,
+        }
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "library": self.library,
+            "unit": self.unit,
+            "kind": self.kind,
+            "entity": self.entity,
+            "testbench": self.testbench,
+            "preferred": self.preferred,
+        })
+    }
+}
@@ -0,0 +1,37 @@
+use crate::classes::BindingOverrides;
+
+pub struct ScriptOptions {
+    pub stop_after: String, // [compile, elaborate, run]
+    pub parallel: bool,
+    pub jobs: usize,
+    pub full: bool,
+    pub stamps_dir: String,
+    pub format: String, // [sh, questa, vivado]
+    pub vhdl_std: String,
+    pub lib_dir: String,
+    pub generics: Vec<(String, String)>, // for {generics} of elaborate and run
+    pub overrides: BindingOverrides,
+}
+
+impl Default for ScriptOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ScriptOptions {
+    pub fn new() -> ScriptOptions {
+        ScriptOptions {
+            stop_after: String::from("run"),
+            parallel: false,
+            jobs: 1,
+            full: false,
+            stamps_dir: String::from(".hanna"),
+            format: String::from("sh"),
+            vhdl_std: String::from("2008"),
+            lib_dir: String::from("work/{library}"),
+            generics: Vec::new(),
+            overrides: BindingOverrides::new(),
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use std::time::Duration;
+
+pub struct StepResult {
+    pub stage: String,
+    pub library: String,
+    pub status: String, // [ok, failed, skipped]
+    pub exit_code: i32,
+    pub duration: Duration,
+    pub failed_file: String,
+    pub timed_out: bool,
+    pub output: Vec<String>,
+}
+
+impl StepResult {
+    pub fn new(stage: &str, library: &str) -> StepResult {
+        StepResult {
+            stage: stage.to_string(),
+            library: library.to_string(),
+            status: String::from("skipped"),
+            exit_code: 0,
+            duration: Duration::ZERO,
+            failed_file: String::new(),
+            timed_out: false,
+            output: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,27 @@
+pub struct TestOptions {
+    pub pattern: String, // glob for the testbenches, lib.unit or unit
+    pub junit: String, // path of the JUnit XML file
+    pub timeout: u64, // seconds, 0: as configured in tool.toml
+    pub fail_patterns: Vec<String>, // in addition to the ones of tool.toml
+    pub jobs: usize,
+    pub generics: Vec<(String, String)>, // for all testbenches, override the ones of tool.toml
+}
+
+impl Default for TestOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TestOptions {
+    pub fn new() -> TestOptions {
+        TestOptions {
+            pattern: String::from("*"),
+            junit: String::from("hanna_junit.xml"),
+            timeout: 0,
+            fail_patterns: Vec::new(),
+            jobs: 1,
+            generics: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use std::time::Duration;
+
+pub struct TestResult {
+    pub toplevel: String,
+    pub generics: Vec<(String, String)>, // of this parameterisation, empty for the default one
+    pub status: String, // [passed, failed, skipped]
+    pub duration: Duration,
+    pub message: String,
+    pub output: Vec<String>,
+}
+
+impl TestResult {
+    pub fn new(toplevel: &str) -> TestResult {
+        TestResult {
+            toplevel: toplevel.to_string(),
+            generics: Vec::new(),
+            status: String::from("skipped"),
+            duration: Duration::ZERO,
+            message: String::new(),
+            output: Vec::new(),
+        }
+    }
+
+    // toplevel and its generics, e.g. lib.cfg_tb[WIDTH=8,SEED=1]
+    pub fn name(&self) -> String {
+        if self.generics.is_empty() {
+            return self.toplevel.clone();
+        }
+        let generics: Vec<String> = self.generics.iter().map(|(n, v)| format!("{}={}", n, v)).collect();
+        format!("{}[{}]", self.toplevel, generics.join(","))
+    }
+}
@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use log::warn;
+
+// value quoted for a shell if needed
+fn quote(value: &str) -> String {
+    shlex::try_quote(value).map_or(value.to_string(), |q| q.to_string())
+}
+
+pub struct ToolCall {
+    pub stage: String, // [before, per_lib, compile, after, elaborate, run]
+    pub library: String, // empty if the call isn't bound to one library
+    pub language: String,
+    pub fragments: Vec<String>,
+    pub files: Vec<String>,
+    pub shell: bool, // exec_* entries are handed over to 'sh -c'
+    pub cwd: String,
+    pub env: HashMap<String, String>,
+    pub timeout: Duration, // the call is killed after it, no limit if zero
+}
+
+impl ToolCall {
+    pub fn new(stage: &str, library: &str, language: &str) -> ToolCall {
+        ToolCall {
+            stage: stage.to_string(),
+            library: library.to_string(),
+            language: language.to_string(),
+            fragments: Vec::new(),
+            files: Vec::new(),
+            shell: false,
+            cwd: String::new(),
+            env: HashMap::new(),
+            timeout: Duration::ZERO,
+        }
+    }
+
+    // call as written into a shell script
+    pub fn to_script(&self) -> String {
+        self.render(" \\\n    ")
+    }
+
+    // call as one line, e.g. for a Makefile recipe
+    pub fn to_line(&self) -> String {
+        self.render(" ")
+    }
+
+    fn render(&self, separator: &str) -> String {
+        let fragments: Vec<&str> = self
+            .fragments
+            .iter()
+            .map(|f| f.trim())
+            .filter(|f| !f.is_empty())
+            .collect();
+        let mut ret = fragments.join(separator);
+        let mut env: Vec<String> = self
+            .env
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, quote(value)))
+            .collect();
+        if !env.is_empty() {
+            env.sort();
+            ret = format!("{} {}", env.join(" "), ret);
+        }
+        if !self.cwd.is_empty() {
+            ret = format!("(cd {} && {})", quote(&self.cwd), ret);
+        }
+        ret
+    }
+
+    // program and arguments for a direct call, files are kept as one argument each
+    pub fn to_args(&self) -> Vec<String> {
+        if self.shell {
+            return vec![
+                String::from("sh"),
+                String::from("-c"),
+                self.fragments.join(" "),
+            ];
+        }
+        let mut ret: Vec<String> = Vec::new();
+        for fragment in &self.fragments {
+            if self.files.contains(fragment) {
+                ret.push(fragment.clone());
+            } else {
+                // split like a shell does, so quoted arguments may contain spaces
+                match shlex::split(fragment) {
+                    Some(args) => ret.extend(args),
+                    None => {
+                        warn!("Unbalanced quotes in '{}', it is split at whitespace", fragment);
+                        ret.extend(fragment.split_whitespace().map(String::from));
+                    }
+                }
+            }
+        }
+        ret
+    }
+}
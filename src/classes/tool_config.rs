@@ -1,15 +1,21 @@
 use std::collections::HashMap;
 
-use crate::classes::ToolLangConfig;
+use crate::classes::{ToolLangConfig, ToolStageConfig, ToolTestConfig};
 
 pub struct ToolConfig {
     pub common: Vec<String>,
     pub vhdl: ToolLangConfig,
     pub verilog: ToolLangConfig,
+    pub elaborate: ToolStageConfig,
+    pub run: ToolStageConfig,
+    pub test: ToolTestConfig,
+    pub generic_format: String, // rendering of one generic for {generics}, takes {name} and {value}
     pub exec_before: Vec<String>,
     pub exec_after: Vec<String>,
     pub exec_per_lib: Vec<String>,
     pub replacement: HashMap<String, String>,
+    pub cwd: String,
+    pub env: HashMap<String, String>,
 }
 /*
 impl Default for ToolConfig {
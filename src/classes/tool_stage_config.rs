@@ -0,0 +1,23 @@
+use std::collections::HashMap;
+
+pub struct ToolStageConfig {
+    pub commands: Vec<String>,
+    pub cwd: String,
+    pub env: HashMap<String, String>,
+}
+
+impl Default for ToolStageConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ToolStageConfig {
+    pub fn new() -> ToolStageConfig {
+        ToolStageConfig {
+            commands: Vec::new(),
+            cwd: String::new(),
+            env: HashMap::new(),
+        }
+    }
+}
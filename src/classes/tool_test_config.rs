@@ -0,0 +1,25 @@
+pub struct ToolTestConfig {
+    pub fail_patterns: Vec<String>, // a test fails if one of these regexes matches a line of its output
+    pub pass_patterns: Vec<String>, // a test fails if none of these regexes matches, unless it's empty
+    pub timeout: u64, // seconds for elaborate and run of one test, 0: unlimited
+    pub run_dir: String, // working directory of each test, takes the toplevel placeholders
+    pub generics: Vec<(String, Vec<(String, String)>)>, // testbench pattern and the generics of one run
+}
+
+impl Default for ToolTestConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ToolTestConfig {
+    pub fn new() -> ToolTestConfig {
+        ToolTestConfig {
+            fail_patterns: Vec::new(),
+            pass_patterns: Vec::new(),
+            timeout: 0,
+            run_dir: String::from(".hanna/test/{toplevel}"),
+            generics: Vec::new(),
+        }
+    }
+}
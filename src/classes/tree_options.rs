@@ -0,0 +1,21 @@
+pub struct TreeOptions {
+    pub depth: usize, // 0: unlimited
+    pub libraries: Vec<String>, // only instances of these libraries and their parents, all if empty
+    pub json: bool,
+}
+
+impl Default for TreeOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TreeOptions {
+    pub fn new() -> TreeOptions {
+        TreeOptions {
+            depth: 0,
+            libraries: Vec::new(),
+            json: false,
+        }
+    }
+}
@@ -33,10 +33,44 @@ pub enum Commands {
     // script
     Script(ScriptCommands),
 
-    // execute: not yet, generate script and execute
-    Execute(ScriptCommands),
-    // tree: not yet
-    //Tree(FilesCommands),
+    // execute: compile, elaborate and run without writing a script
+    Execute(ExecuteCommands),
+
+    // Makefile with one stamp target per file
+    Make(MakeCommands),
+
+    // build.ninja with one stamp target per file
+    Ninja(NinjaCommands),
+
+    // library mapping files for simulators
+    Libmap(LibmapCommands),
+
+    // vhdl_ls.toml for the VHDL language server
+    VhdlLs(VhdlLsCommands),
+
+    // instance hierarchy
+    Tree(TreeCommands),
+
+    // hierarchy, unit or library graph for Graphviz or Mermaid
+    Graph(GraphCommands),
+
+    // why a file or unit is needed by the toplevel
+    Why(WhyCommands),
+
+    // toplevels affected by changed files
+    Affected(AffectedCommands),
+
+    // files and units no toplevel needs
+    Unused(UnusedCommands),
+
+    // compile, elaborate and run the testbenches
+    Test(TestCommands),
+
+    // VHDL configuration binding every instance of the resolved hierarchy
+    GenConfig(GenConfigCommands),
+
+    // consistency of configurations and component declarations with the units they bind
+    Check(CheckCommands),
 }
 
 #[derive(Debug, Args)]
@@ -55,12 +89,25 @@ pub struct InfoCommands {
     /// path to tool.toml
     #[arg(short, long, default_value_t = String::from("tool.toml"))]
     pub tool: String,
+
+    /// units no other unit instantiates or configures, of all libraries if none is given
+    #[arg(long)]
+    pub roots: bool,
+
+    /// print the roots as JSON
+    #[arg(long, requires = "roots")]
+    pub json: bool,
 }
 
 #[derive(Debug, Args)]
 pub struct FilesCommands {
-    #[arg()]
-    pub toplevel: String,
+    /// one or more toplevels, glob patterns like lib.cfg_tb_* are allowed
+    #[arg(required_unless_present = "all", conflicts_with = "all", num_args = 1..)]
+    pub toplevel: Vec<String>,
+
+    /// all libraries completely instead of a toplevel
+    #[arg(long)]
+    pub all: bool,
 
     /// replacements in toml files of form {var}, format is var=new_value
     #[arg(short, long)]
@@ -81,12 +128,25 @@ pub struct FilesCommands {
     /// force to compile library in arbitrary order (could be useful if Verilog has needed submodules)
     #[arg(short, long)]
     pub force: Vec<String>,
+
+    /// binding of an instance, format is label.path=lib.entity(arch) or label.path=lib.configuration
+    #[arg(long, conflicts_with = "all")]
+    pub bind: Vec<String>,
+
+    /// architecture of an entity without explicit binding, format is [lib.]entity=arch
+    #[arg(long, conflicts_with = "all")]
+    pub arch: Vec<String>,
 }
 
 #[derive(Debug, Args)]
 pub struct JSONCommands {
-    #[arg()]
-    pub toplevel: String,
+    /// one or more toplevels, glob patterns like lib.cfg_tb_* are allowed
+    #[arg(required_unless_present_any = ["schema", "all"], conflicts_with = "all", num_args = 1..)]
+    pub toplevel: Vec<String>,
+
+    /// all libraries completely instead of a toplevel
+    #[arg(long)]
+    pub all: bool,
 
     /// replacements in toml files of form {var}, format is var=new_value
     #[arg(short, long)]
@@ -107,12 +167,29 @@ pub struct JSONCommands {
     /// force to compile library in arbitrary order (could be useful if Verilog has needed submodules)
     #[arg(short, long)]
     pub force: Vec<String>,
+
+    /// print the JSON Schema of the written file
+    #[arg(long)]
+    pub schema: bool,
+
+    /// binding of an instance, format is label.path=lib.entity(arch) or label.path=lib.configuration
+    #[arg(long, conflicts_with = "all")]
+    pub bind: Vec<String>,
+
+    /// architecture of an entity without explicit binding, format is [lib.]entity=arch
+    #[arg(long, conflicts_with = "all")]
+    pub arch: Vec<String>,
 }
 
 #[derive(Debug, Args)]
 pub struct ScriptCommands {
-    #[arg()]
-    pub toplevel: String,
+    /// one or more toplevels, glob patterns like lib.cfg_tb_* are allowed
+    #[arg(required_unless_present = "all", conflicts_with = "all", num_args = 1..)]
+    pub toplevel: Vec<String>,
+
+    /// all libraries completely instead of a toplevel
+    #[arg(long)]
+    pub all: bool,
 
     /// replacements in toml files of form {var}, format is var=new_value
     #[arg(short, long)]
@@ -133,14 +210,537 @@ pub struct ScriptCommands {
     /// force to compile library in arbitrary order (could be useful if Verilog has needed submodules)
     #[arg(short, long)]
     pub force: Vec<String>,
+
+    /// last stage to put into the script: compile, elaborate or run
+    #[arg(long, default_value_t = String::from("run"), value_parser = ["compile", "elaborate", "run"])]
+    pub stop_after: String,
+
+    /// compile independent libraries as parallel background jobs
+    #[arg(long)]
+    pub parallel: bool,
+
+    /// script format: shell script, Questa .do file or Vivado Tcl script
+    #[arg(long, default_value_t = String::from("sh"), value_parser = ["sh", "questa", "vivado"])]
+    pub format: String,
+
+    /// VHDL standard for the questa and vivado formats
+    #[arg(long, default_value_t = String::from("2008"), value_parser = ["93", "2002", "2008", "2019"])]
+    pub vhdl_std: String,
+
+    /// physical directory of a library for the questa format
+    #[arg(long, default_value_t = String::from("work/{library}"))]
+    pub lib_dir: String,
+
+    /// generic of the toplevel for {generics} in elaborate and run, format is name=value
+    #[arg(short, long)]
+    pub generic: Vec<String>,
+
+    /// binding of an instance, format is label.path=lib.entity(arch) or label.path=lib.configuration
+    #[arg(long, conflicts_with = "all")]
+    pub bind: Vec<String>,
+
+    /// architecture of an entity without explicit binding, format is [lib.]entity=arch
+    #[arg(long, conflicts_with = "all")]
+    pub arch: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct LibmapCommands {
+    #[arg()]
+    pub toplevel: String,
+
+    /// replacements in toml files of form {var}, format is var=new_value
+    #[arg(short, long)]
+    pub replacement: Vec<String>,
+
+    /// path to libraries.toml
+    #[arg(short, long, default_value_t = String::from("libraries.toml"))]
+    pub libraries: String,
+
+    /// path to tool.toml
+    #[arg(short, long, default_value_t = String::from("tool.toml"))]
+    pub tool: String,
+
+    /// path where to output files
+    #[arg(short, long, default_value_t = String::from("./"))]
+    pub path: String,
+
+    /// force to compile library in arbitrary order (could be useful if Verilog has needed submodules)
+    #[arg(short, long)]
+    pub force: Vec<String>,
+
+    /// physical directory of a library
+    #[arg(long, default_value_t = String::from("work/{library}"))]
+    pub lib_dir: String,
+
+    /// which mapping files to write
+    #[arg(long, default_value_t = String::from("all"), value_parser = ["all", "modelsim", "cadence", "synopsys"])]
+    pub format: String,
+
+    /// binding of an instance, format is label.path=lib.entity(arch) or label.path=lib.configuration
+    #[arg(long)]
+    pub bind: Vec<String>,
+
+    /// architecture of an entity without explicit binding, format is [lib.]entity=arch
+    #[arg(long)]
+    pub arch: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct ExecuteCommands {
+    #[arg()]
+    pub toplevel: String,
+
+    /// replacements in toml files of form {var}, format is var=new_value
+    #[arg(short, long)]
+    pub replacement: Vec<String>,
+
+    /// path to libraries.toml
+    #[arg(short, long, default_value_t = String::from("libraries.toml"))]
+    pub libraries: String,
+
+    /// path to tool.toml
+    #[arg(short, long, default_value_t = String::from("tool.toml"))]
+    pub tool: String,
+
+    /// force to compile library in arbitrary order (could be useful if Verilog has needed submodules)
+    #[arg(short, long)]
+    pub force: Vec<String>,
+
+    /// last stage to execute: compile, elaborate or run
+    #[arg(long, default_value_t = String::from("run"), value_parser = ["compile", "elaborate", "run"])]
+    pub stop_after: String,
+
+    /// number of libraries compiled in parallel
+    #[arg(short, long, default_value_t = 1)]
+    pub jobs: usize,
+
+    /// compile all files, not only the changed ones and their dependents
+    #[arg(long)]
+    pub full: bool,
+
+    /// directory of the stamp files used to find changed files
+    #[arg(long, default_value_t = String::from(".hanna"))]
+    pub stamps: String,
+
+    /// generic of the toplevel for {generics} in elaborate and run, format is name=value
+    #[arg(short, long)]
+    pub generic: Vec<String>,
+
+    /// binding of an instance, format is label.path=lib.entity(arch) or label.path=lib.configuration
+    #[arg(long)]
+    pub bind: Vec<String>,
+
+    /// architecture of an entity without explicit binding, format is [lib.]entity=arch
+    #[arg(long)]
+    pub arch: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct MakeCommands {
+    #[arg()]
+    pub toplevel: String,
+
+    /// replacements in toml files of form {var}, format is var=new_value
+    #[arg(short, long)]
+    pub replacement: Vec<String>,
+
+    /// path to libraries.toml
+    #[arg(short, long, default_value_t = String::from("libraries.toml"))]
+    pub libraries: String,
+
+    /// path to tool.toml
+    #[arg(short, long, default_value_t = String::from("tool.toml"))]
+    pub tool: String,
+
+    /// Makefile path
+    #[arg(short, long, default_value_t = String::from("Makefile"))]
+    pub name: String,
+
+    /// force to compile library in arbitrary order (could be useful if Verilog has needed submodules)
+    #[arg(short, long)]
+    pub force: Vec<String>,
+
+    /// default directory of the stamp files (HANNA_STAMPS in the Makefile)
+    #[arg(long, default_value_t = String::from(".hanna/make"))]
+    pub stamps: String,
+
+    /// binding of an instance, format is label.path=lib.entity(arch) or label.path=lib.configuration
+    #[arg(long)]
+    pub bind: Vec<String>,
+
+    /// architecture of an entity without explicit binding, format is [lib.]entity=arch
+    #[arg(long)]
+    pub arch: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct NinjaCommands {
+    #[arg()]
+    pub toplevel: String,
+
+    /// replacements in toml files of form {var}, format is var=new_value
+    #[arg(short, long)]
+    pub replacement: Vec<String>,
+
+    /// path to libraries.toml
+    #[arg(short, long, default_value_t = String::from("libraries.toml"))]
+    pub libraries: String,
+
+    /// path to tool.toml
+    #[arg(short, long, default_value_t = String::from("tool.toml"))]
+    pub tool: String,
+
+    /// ninja file path
+    #[arg(short, long, default_value_t = String::from("build.ninja"))]
+    pub name: String,
+
+    /// force to compile library in arbitrary order (could be useful if Verilog has needed submodules)
+    #[arg(short, long)]
+    pub force: Vec<String>,
+
+    /// directory of the stamp files
+    #[arg(long, default_value_t = String::from(".hanna/ninja"))]
+    pub stamps: String,
+
+    /// binding of an instance, format is label.path=lib.entity(arch) or label.path=lib.configuration
+    #[arg(long)]
+    pub bind: Vec<String>,
+
+    /// architecture of an entity without explicit binding, format is [lib.]entity=arch
+    #[arg(long)]
+    pub arch: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct VhdlLsCommands {
+    /// only the files this toplevel needs, all libraries if not given
+    #[arg()]
+    pub toplevel: Option<String>,
+
+    /// replacements in toml files of form {var}, format is var=new_value
+    #[arg(short, long)]
+    pub replacement: Vec<String>,
+
+    /// path to libraries.toml
+    #[arg(short, long, default_value_t = String::from("libraries.toml"))]
+    pub libraries: String,
+
+    /// path to tool.toml
+    #[arg(short, long, default_value_t = String::from("tool.toml"))]
+    pub tool: String,
+
+    /// vhdl_ls.toml path
+    #[arg(short, long, default_value_t = String::from("vhdl_ls.toml"))]
+    pub name: String,
+
+    /// binding of an instance, format is label.path=lib.entity(arch) or label.path=lib.configuration
+    #[arg(long)]
+    pub bind: Vec<String>,
+
+    /// architecture of an entity without explicit binding, format is [lib.]entity=arch
+    #[arg(long)]
+    pub arch: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct TreeCommands {
+    #[arg()]
+    pub toplevel: String,
+
+    /// replacements in toml files of form {var}, format is var=new_value
+    #[arg(short, long)]
+    pub replacement: Vec<String>,
+
+    /// path to libraries.toml
+    #[arg(short, long, default_value_t = String::from("libraries.toml"))]
+    pub libraries: String,
+
+    /// path to tool.toml
+    #[arg(short, long, default_value_t = String::from("tool.toml"))]
+    pub tool: String,
+
+    /// number of instance levels to show, 0 shows all
+    #[arg(short, long, default_value_t = 0)]
+    pub depth: usize,
+
+    /// only show instances of this library and their parents, can be given several times
+    #[arg(long)]
+    pub library: Vec<String>,
+
+    /// print the tree as JSON
+    #[arg(long)]
+    pub json: bool,
+
+    /// binding of an instance, format is label.path=lib.entity(arch) or label.path=lib.configuration
+    #[arg(long)]
+    pub bind: Vec<String>,
+
+    /// architecture of an entity without explicit binding, format is [lib.]entity=arch
+    #[arg(long)]
+    pub arch: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct GraphCommands {
+    #[arg()]
+    pub toplevel: String,
+
+    /// replacements in toml files of form {var}, format is var=new_value
+    #[arg(short, long)]
+    pub replacement: Vec<String>,
+
+    /// path to libraries.toml
+    #[arg(short, long, default_value_t = String::from("libraries.toml"))]
+    pub libraries: String,
+
+    /// path to tool.toml
+    #[arg(short, long, default_value_t = String::from("tool.toml"))]
+    pub tool: String,
+
+    /// instance hierarchy, design unit dependencies or library dependencies
+    #[arg(long, default_value_t = String::from("hierarchy"), value_parser = ["hierarchy", "units", "libraries"])]
+    pub kind: String,
+
+    /// output format
+    #[arg(long, default_value_t = String::from("dot"), value_parser = ["dot", "mermaid"])]
+    pub format: String,
+
+    /// binding of an instance, format is label.path=lib.entity(arch) or label.path=lib.configuration
+    #[arg(long)]
+    pub bind: Vec<String>,
+
+    /// architecture of an entity without explicit binding, format is [lib.]entity=arch
+    #[arg(long)]
+    pub arch: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct WhyCommands {
+    #[arg()]
+    pub toplevel: String,
+
+    /// file (with path or extension) or unit (name or library.name)
+    #[arg()]
+    pub target: String,
+
+    /// replacements in toml files of form {var}, format is var=new_value
+    #[arg(short, long)]
+    pub replacement: Vec<String>,
+
+    /// path to libraries.toml
+    #[arg(short, long, default_value_t = String::from("libraries.toml"))]
+    pub libraries: String,
+
+    /// path to tool.toml
+    #[arg(short, long, default_value_t = String::from("tool.toml"))]
+    pub tool: String,
+
+    /// binding of an instance, format is label.path=lib.entity(arch) or label.path=lib.configuration
+    #[arg(long)]
+    pub bind: Vec<String>,
+
+    /// architecture of an entity without explicit binding, format is [lib.]entity=arch
+    #[arg(long)]
+    pub arch: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct GenConfigCommands {
+    /// architecture to configure
+    #[arg()]
+    pub toplevel: String,
+
+    /// name of the configuration, cfg_<entity> if not given
+    #[arg(short, long, default_value_t = String::from(""))]
+    pub name: String,
+
+    /// path of the written file, <name>.vhd if not given
+    #[arg(short, long, default_value_t = String::from(""))]
+    pub output: String,
+
+    /// replacements in toml files of form {var}, format is var=new_value
+    #[arg(short, long)]
+    pub replacement: Vec<String>,
+
+    /// path to libraries.toml
+    #[arg(short, long, default_value_t = String::from("libraries.toml"))]
+    pub libraries: String,
+
+    /// path to tool.toml
+    #[arg(short, long, default_value_t = String::from("tool.toml"))]
+    pub tool: String,
+
+    /// binding of an instance, format is label.path=lib.entity(arch) or label.path=lib.configuration
+    #[arg(long)]
+    pub bind: Vec<String>,
+
+    /// architecture of an entity without explicit binding, format is [lib.]entity=arch
+    #[arg(long)]
+    pub arch: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct CheckCommands {
+    /// only the configurations of this library
+    #[arg(default_value_t = String::from(""))]
+    pub library: String,
+
+    /// replacements in toml files of form {var}, format is var=new_value
+    #[arg(short, long)]
+    pub replacement: Vec<String>,
+
+    /// path to libraries.toml
+    #[arg(short, long, default_value_t = String::from("libraries.toml"))]
+    pub libraries: String,
+
+    /// path to tool.toml
+    #[arg(short, long, default_value_t = String::from("tool.toml"))]
+    pub tool: String,
+
+    /// print the problems as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct AffectedCommands {
+    /// changed files
+    #[arg(long, num_args = 1.., required_unless_present = "git_diff")]
+    pub changed: Vec<String>,
+
+    /// take the changed files from 'git diff --name-only <rev>'
+    #[arg(long, value_name = "REV")]
+    pub git_diff: Option<String>,
+
+    /// replacements in toml files of form {var}, format is var=new_value
+    #[arg(short, long)]
+    pub replacement: Vec<String>,
+
+    /// path to libraries.toml
+    #[arg(short, long, default_value_t = String::from("libraries.toml"))]
+    pub libraries: String,
+
+    /// path to tool.toml
+    #[arg(short, long, default_value_t = String::from("tool.toml"))]
+    pub tool: String,
+
+    /// print the result as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct UnusedCommands {
+    /// one or more toplevels, glob patterns like lib.cfg_tb_* are allowed
+    #[arg(required = true, num_args = 1..)]
+    pub toplevel: Vec<String>,
+
+    /// replacements in toml files of form {var}, format is var=new_value
+    #[arg(short, long)]
+    pub replacement: Vec<String>,
+
+    /// path to libraries.toml
+    #[arg(short, long, default_value_t = String::from("libraries.toml"))]
+    pub libraries: String,
+
+    /// path to tool.toml
+    #[arg(short, long, default_value_t = String::from("tool.toml"))]
+    pub tool: String,
+
+    /// print the result as JSON
+    #[arg(long)]
+    pub json: bool,
+
+    /// binding of an instance, format is label.path=lib.entity(arch) or label.path=lib.configuration
+    #[arg(long)]
+    pub bind: Vec<String>,
+
+    /// architecture of an entity without explicit binding, format is [lib.]entity=arch
+    #[arg(long)]
+    pub arch: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct TestCommands {
+    /// glob pattern for the testbenches, lib.unit or unit
+    #[arg(default_value_t = String::from("*"))]
+    pub pattern: String,
+
+    /// replacements in toml files of form {var}, format is var=new_value
+    #[arg(short, long)]
+    pub replacement: Vec<String>,
+
+    /// path to libraries.toml
+    #[arg(short, long, default_value_t = String::from("libraries.toml"))]
+    pub libraries: String,
+
+    /// path to tool.toml
+    #[arg(short, long, default_value_t = String::from("tool.toml"))]
+    pub tool: String,
+
+    /// seconds for elaborate and run of each testbench, overrides timeout of [test] in tool.toml
+    #[arg(long, default_value_t = 0)]
+    pub timeout: u64,
+
+    /// regex which makes a testbench fail if a line of its output matches, in addition to fail_patterns in tool.toml
+    #[arg(long)]
+    pub fail_pattern: Vec<String>,
+
+    /// path of the JUnit XML file
+    #[arg(long, default_value_t = String::from("hanna_junit.xml"))]
+    pub junit: String,
+
+    /// number of libraries compiled in parallel
+    #[arg(short, long, default_value_t = 1)]
+    pub jobs: usize,
+
+    /// generic of the toplevel for {generics} in elaborate and run, format is name=value
+    #[arg(short, long)]
+    pub generic: Vec<String>,
+
+    /// binding of an instance, format is label.path=lib.entity(arch) or label.path=lib.configuration
+    #[arg(long)]
+    pub bind: Vec<String>,
+
+    /// architecture of an entity without explicit binding, format is [lib.]entity=arch
+    #[arg(long)]
+    pub arch: Vec<String>,
 }
 
+#[derive(Default)]
 pub struct ArgsStruct {
     pub command: String,
     pub toplevel: String,
+    pub toplevels: Vec<String>, // of the commands taking several toplevels
+    pub all: bool, // all libraries completely instead of toplevels
     pub libraries: String,
     pub tool: String,
     pub replacement: Vec<String>,
     pub filename: String,
     pub forces: Vec<String>,
+    pub stop_after: String,
+    pub parallel: bool,
+    pub jobs: usize,
+    pub full: bool,
+    pub stamps_dir: String,
+    pub format: String,
+    pub vhdl_std: String,
+    pub lib_dir: String,
+    pub depth: usize,
+    pub library_filter: Vec<String>,
+    pub json: bool,
+    pub roots: bool,
+    pub kind: String,
+    pub target: String,
+    pub changed: Vec<String>,
+    pub git_diff: String,
+    pub pattern: String,
+    pub timeout: u64,
+    pub fail_patterns: Vec<String>,
+    pub junit: String,
+    pub generics: Vec<String>,
+    pub binds: Vec<String>,
+    pub archs: Vec<String>,
+    pub config_name: String,
 }
@@ -0,0 +1,314 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read};
+#[cfg(unix)]
+use std::os::unix::process::CommandExt;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::sync::mpsc::{RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use log::{debug, warn};
+
+use crate::classes::{StepResult, ToolCall};
+
+fn forward_lines<R: Read + Send + 'static>(reader: R, is_stderr: bool, tx: Sender<(bool, String)>) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        for line in BufReader::new(reader).lines().map_while(Result::ok) {
+            if tx.send((is_stderr, line)).is_err() {
+                break;
+            }
+        }
+    })
+}
+
+// first file of the call which is mentioned in the output
+fn find_failed_file(call: &ToolCall, output: &Vec<String>) -> String {
+    for line in output {
+        for file in &call.files {
+            let base = Path::new(file)
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or(file);
+            if line.contains(file.as_str()) || line.contains(base) {
+                return file.clone();
+            }
+        }
+    }
+    String::new()
+}
+
+// kills the process and everything it started, e.g. the simulator started by 'sh -c'
+fn kill(child: &mut Child) {
+    #[cfg(unix)]
+    {
+        // the call is the leader of its own process group
+        let Ok(pgid) = libc::pid_t::try_from(child.id()) else {
+            warn!("Process id {} is out of range, only the process is killed", child.id());
+            kill_process(child);
+            return;
+        };
+        // SAFETY: killpg only sends a signal, the group was created for this call
+        if unsafe { libc::killpg(pgid, libc::SIGKILL) } == 0 {
+            return;
+        }
+        warn!("Can't kill process group {}: {}", pgid, std::io::Error::last_os_error());
+    }
+    kill_process(child);
+}
+
+fn kill_process(child: &mut Child) {
+    if let Err(err) = child.kill() {
+        warn!("Can't kill process {}: {}", child.id(), err);
+    }
+}
+
+pub fn run_tool_call(call: &ToolCall) -> StepResult {
+    let mut result = StepResult::new(&call.stage, &call.library);
+    let args = call.to_args();
+    if args.is_empty() {
+        result.status = String::from("ok");
+        return result;
+    }
+    let prefix = if call.library.is_empty() {
+        format!("[{}]", call.stage)
+    } else {
+        format!("[{} {}]", call.stage, call.library)
+    };
+    debug!("{} {:?}", prefix, args);
+    let mut cmd = Command::new(&args[0]);
+    cmd.args(&args[1..])
+        .envs(&call.env)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if !call.cwd.is_empty() {
+        cmd.current_dir(&call.cwd);
+    }
+    // a call with a timeout gets its own process group, so it can be killed completely
+    #[cfg(unix)]
+    if !call.timeout.is_zero() {
+        cmd.process_group(0);
+    }
+    let start = Instant::now();
+    let mut child = match cmd.spawn() {
+        Ok(child) => child,
+        Err(err) => {
+            eprintln!("{} Can't start '{}': {}", prefix, args[0], err);
+            result.status = String::from("failed");
+            result.exit_code = 127;
+            result.output.push(err.to_string());
+            return result;
+        }
+    };
+    let (tx, rx) = mpsc::channel();
+    let mut readers = Vec::new();
+    if let Some(stdout) = child.stdout.take() {
+        readers.push(forward_lines(stdout, false, tx.clone()));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        readers.push(forward_lines(stderr, true, tx.clone()));
+    }
+    drop(tx);
+    loop {
+        let received = if call.timeout.is_zero() {
+            rx.recv().map_err(|_| RecvTimeoutError::Disconnected)
+        } else {
+            rx.recv_timeout(call.timeout.saturating_sub(start.elapsed()).max(Duration::from_millis(1)))
+        };
+        match received {
+            Ok((is_stderr, line)) => {
+                if is_stderr {
+                    eprintln!("{} {}", prefix, line);
+                } else {
+                    println!("{} {}", prefix, line);
+                }
+                result.output.push(line);
+            }
+            Err(RecvTimeoutError::Timeout) if start.elapsed() >= call.timeout => {
+                eprintln!("{} Timeout after {:.0}s", prefix, call.timeout.as_secs_f64());
+                kill(&mut child);
+                result.timed_out = true;
+                break;
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+    // processes started by a killed one could still hold the pipes
+    if !result.timed_out {
+        for reader in readers {
+            let _ = reader.join();
+        }
+    }
+    let status = child.wait();
+    result.duration = start.elapsed();
+    if result.timed_out {
+        result.status = String::from("failed");
+        result.exit_code = 124;
+        return result;
+    }
+    match status {
+        Ok(exit_status) if exit_status.success() => {
+            result.status = String::from("ok");
+        }
+        Ok(exit_status) => {
+            result.status = String::from("failed");
+            result.exit_code = exit_status.code().unwrap_or(1);
+            result.failed_file = find_failed_file(call, &result.output);
+        }
+        Err(err) => {
+            eprintln!("{} {}", prefix, err);
+            result.status = String::from("failed");
+            result.exit_code = 1;
+        }
+    }
+    result
+}
+
+// runs the calls and stops at the first failure, compile calls of independent
+// libraries are run in parallel if jobs is greater than 1
+pub fn run_tool_calls(
+    calls: &[ToolCall], dependencies: &HashMap<String, Vec<String>>, jobs: usize,
+) -> Vec<StepResult> {
+    let mut ret: Vec<StepResult> = Vec::new();
+    let mut failed = false;
+    let mut i = 0;
+    while i < calls.len() {
+        let call = &calls[i];
+        if failed {
+            ret.push(StepResult::new(&call.stage, &call.library));
+            i += 1;
+            continue;
+        }
+        if jobs > 1 && is_parallel_compile(call, dependencies) {
+            let mut j = i;
+            while j < calls.len() && is_parallel_compile(&calls[j], dependencies) {
+                j += 1;
+            }
+            let results = run_parallel_compile(&calls[i..j], dependencies, jobs);
+            failed = results.iter().any(|r| r.status == "failed");
+            ret.extend(results);
+            i = j;
+            continue;
+        }
+        let result = run_tool_call(call);
+        failed = result.status == "failed";
+        ret.push(result);
+        i += 1;
+    }
+    ret
+}
+
+fn is_parallel_compile(call: &ToolCall, dependencies: &HashMap<String, Vec<String>>) -> bool {
+    call.stage == "compile" && dependencies.contains_key(&call.library)
+}
+
+// schedules the calls library by library as soon as all libraries they depend on are done
+fn run_parallel_compile(
+    calls: &[ToolCall], dependencies: &HashMap<String, Vec<String>>, jobs: usize,
+) -> Vec<StepResult> {
+    let mut pending: Vec<String> = Vec::new();
+    for call in calls {
+        if !pending.contains(&call.library) {
+            pending.push(call.library.clone());
+        }
+    }
+    let in_block = pending.clone();
+    let mut done: Vec<String> = Vec::new();
+    let mut results: HashMap<usize, StepResult> = HashMap::new();
+    let mut failed = false;
+    let mut running = 0;
+    let (tx, rx) = mpsc::channel::<(String, Vec<(usize, StepResult)>)>();
+    thread::scope(|scope| {
+        loop {
+            while !failed && running < jobs {
+                let Some(pos) = pending.iter().position(|lib| {
+                    dependencies.get(lib).is_none_or(|deps| {
+                        deps.iter().all(|dep| !in_block.contains(dep) || done.contains(dep))
+                    })
+                }) else {
+                    break;
+                };
+                let lib = pending.remove(pos);
+                let tx = tx.clone();
+                running += 1;
+                scope.spawn(move || {
+                    let mut lib_results: Vec<(usize, StepResult)> = Vec::new();
+                    let mut lib_failed = false;
+                    for (index, call) in calls.iter().enumerate().filter(|(_, c)| c.library == lib) {
+                        if lib_failed {
+                            lib_results.push((index, StepResult::new(&call.stage, &call.library)));
+                            continue;
+                        }
+                        let result = run_tool_call(call);
+                        lib_failed = result.status == "failed";
+                        lib_results.push((index, result));
+                    }
+                    let _ = tx.send((lib, lib_results));
+                });
+            }
+            if running == 0 {
+                break;
+            }
+            let Ok((lib, lib_results)) = rx.recv() else {
+                break;
+            };
+            running -= 1;
+            for (index, result) in lib_results {
+                failed |= result.status == "failed";
+                results.insert(index, result);
+            }
+            done.push(lib);
+        }
+    });
+    calls
+        .iter()
+        .enumerate()
+        .map(|(index, call)| {
+            results
+                .remove(&index)
+                .unwrap_or_else(|| StepResult::new(&call.stage, &call.library))
+        })
+        .collect()
+}
+
+pub fn print_summary(results: &[StepResult]) {
+    println!();
+    for line in summary_lines(results) {
+        println!("{}", line);
+    }
+}
+
+// table of the stage, library, status and time of each call
+pub fn summary_lines(results: &[StepResult]) -> Vec<String> {
+    let lib_width = results
+        .iter()
+        .map(|r| r.library.len())
+        .max()
+        .unwrap_or(0)
+        .max("Library".len());
+    let mut ret: Vec<String> = vec![format!("{:<10} {:<lib_width$} {:<8} {:>9}  Details", "Stage", "Library", "Status", "Time")];
+    for result in results {
+        let time = if result.status == "skipped" {
+            String::new()
+        } else {
+            format!("{:.2}s", result.duration.as_secs_f64())
+        };
+        let mut details = String::new();
+        if result.timed_out {
+            details = String::from("timeout");
+        } else if result.status == "failed" {
+            details = format!("exit code {}", result.exit_code);
+            if !result.failed_file.is_empty() {
+                details.push_str(&format!(", {}", result.failed_file));
+            }
+        }
+        let line = format!(
+            "{:<10} {:<lib_width$} {:<8} {:>9}  {}",
+            result.stage, result.library, result.status, time, details
+        );
+        ret.push(line.trim_end().to_string());
+    }
+    ret
+}
@@ -0,0 +1,182 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::process::exit;
+
+use log::{error, warn};
+
+use crate::classes::{BindingOverrides, HierarchyNode, Library, RE_COMMENT, RE_CONF_SPEC, RE_END_GENERATE, RE_GENERATE_LABEL, RE_INSTANCE};
+use crate::{get_architecture_body, get_hierarchy, get_single_element_list};
+
+// an instance as written in the architecture
+struct InstanceSource {
+    component: String,      // empty for entity and configuration instantiations
+    generates: Vec<String>, // labels of the enclosing generate statements, outermost first
+    specified: bool,        // bound by a configuration specification of the architecture
+}
+
+// instances of an architecture by label, the generates and blocks are stripped before resolving so the source is read again
+fn get_instance_sources(filename: &str, entity: &str, arch: &str) -> HashMap<String, InstanceSource> {
+    let mut ret: HashMap<String, InstanceSource> = HashMap::new();
+    let Ok(bytes) = fs::read(filename) else {
+        return ret;
+    };
+    let content = RE_COMMENT.replace_all(&String::from_utf8_lossy(&bytes), "").to_string();
+    let Some(body) = get_architecture_body(&content, entity, arch) else {
+        return ret;
+    };
+
+    // (position, label of a generate or block or None for its end)
+    let mut events: Vec<(usize, Option<String>)> = RE_GENERATE_LABEL
+        .captures_iter(body)
+        .map(|caps| (caps.get(0).map_or(0, |m| m.start()), Some(caps["label"].to_ascii_lowercase())))
+        .collect();
+    events.extend(RE_END_GENERATE.find_iter(body).map(|m| (m.start(), None)));
+    events.sort_by_key(|(pos, _)| *pos);
+    let specs: Vec<(Vec<String>, String)> = RE_CONF_SPEC
+        .captures_iter(body)
+        .map(|caps| {
+            let labels = caps["labels"].split(',').map(|l| l.trim().to_ascii_lowercase()).collect();
+            (labels, caps["comp"].to_ascii_lowercase())
+        })
+        .collect();
+
+    for caps in RE_INSTANCE.captures_iter(body) {
+        let pos = caps.get(0).map_or(0, |m| m.start());
+        let mut generates: Vec<String> = Vec::new();
+        for (_, event) in events.iter().take_while(|(p, _)| *p < pos) {
+            match event {
+                Some(label) => generates.push(label.clone()),
+                None => {
+                    generates.pop();
+                }
+            }
+        }
+        let label = caps["label"].to_ascii_lowercase();
+        let component = caps.name("c_name").map_or("", |m| m.as_str()).to_ascii_lowercase();
+        let specified = !component.is_empty()
+            && specs.iter().any(|(labels, comp)| {
+                comp == &component && labels.iter().any(|l| l == &label || l == "all" || l == "others")
+            });
+        ret.entry(label).or_insert(InstanceSource { component, generates, specified });
+    }
+    ret
+}
+
+// component configuration of one instance
+fn instance_lines(
+    child: &HierarchyNode, source: Option<&InstanceSource>, libraries: &HashMap<String, Library>,
+    used_libraries: &mut BTreeSet<String>,
+) -> Vec<String> {
+    let target = format!("{}.{}", child.library, child.unit);
+    let Some(source) = source.filter(|s| !s.component.is_empty()) else {
+        return vec![format!("-- {}: {} is instantiated directly and can't be configured", child.label, target)];
+    };
+    if child.binding == "unresolved" {
+        warn!("Instance {} of component {} is unresolved", child.label, source.component);
+        return vec![format!("-- {}: component {} is unresolved", child.label, source.component)];
+    }
+    let mut lines: Vec<String> = vec![format!("for {} : {}", child.label, source.component)];
+    // a configuration specification can't be overridden, only the block below it is configured
+    if !source.specified {
+        used_libraries.insert(child.library.clone());
+        let is_configuration = libraries.get(&child.library).is_some_and(|l| l.configurations.contains_key(&child.unit));
+        let aspect = if is_configuration { "configuration" } else { "entity" };
+        lines.push(format!("  use {} {};", aspect, target));
+    }
+    if child.language == "vhdl" {
+        lines.extend(block_lines(child, libraries, used_libraries).iter().map(|l| format!("  {}", l)));
+    }
+    lines.push(String::from("end for;"));
+    lines
+}
+
+// block configuration of the architecture of node, empty if there is nothing to configure
+fn block_lines(node: &HierarchyNode, libraries: &HashMap<String, Library>, used_libraries: &mut BTreeSet<String>) -> Vec<String> {
+    let Some((entity, arch)) = node.unit.strip_suffix(')').and_then(|u| u.split_once('(')) else {
+        return Vec::new();
+    };
+    let sources = get_instance_sources(&node.filename, entity, arch);
+    let mut items: Vec<(Vec<String>, Vec<String>)> = Vec::new();
+    for child in node.children.iter().filter(|c| c.kind == "instance") {
+        let source = sources.get(&child.label);
+        let generates = source.map(|s| s.generates.clone()).unwrap_or_default();
+        items.push((generates, instance_lines(child, source, libraries, used_libraries)));
+    }
+    if items.is_empty() {
+        return Vec::new();
+    }
+    let mut lines: Vec<String> = vec![format!("for {}", arch)];
+    nest_generates(&items, 0, "  ", &mut lines);
+    lines.push(String::from("end for;"));
+    lines
+}
+
+// instances inside generates get a block configuration of each generate
+fn nest_generates(items: &[(Vec<String>, Vec<String>)], level: usize, indent: &str, lines: &mut Vec<String>) {
+    let mut done: Vec<&String> = Vec::new();
+    for (generates, item_lines) in items {
+        if generates.len() == level {
+            lines.extend(item_lines.iter().map(|l| format!("{}{}", indent, l)));
+            continue;
+        }
+        let generate = &generates[level];
+        if done.contains(&generate) {
+            continue;
+        }
+        done.push(generate);
+        let inner: Vec<(Vec<String>, Vec<String>)> = items
+            .iter()
+            .filter(|(g, _)| g.len() > level && &g[level] == generate)
+            .cloned()
+            .collect();
+        lines.push(format!("{}for {}", indent, generate));
+        nest_generates(&inner, level + 1, &format!("{}  ", indent), lines);
+        lines.push(format!("{}end for;", indent));
+    }
+}
+
+// configuration declaration binding every instance of the hierarchy explicitly
+pub fn get_configuration(hierarchy: &HierarchyNode, libraries: &HashMap<String, Library>, name: &str) -> Vec<String> {
+    let entity = hierarchy.unit.split_once('(').map_or(hierarchy.unit.as_str(), |(e, _)| e);
+    let mut used_libraries: BTreeSet<String> = BTreeSet::from([hierarchy.library.clone()]);
+    let mut block = block_lines(hierarchy, libraries, &mut used_libraries);
+    if block.is_empty() {
+        // a configuration needs the block of the architecture even without instances
+        let arch = hierarchy.unit.trim_end_matches(')').split_once('(').map_or("", |(_, a)| a);
+        block = vec![format!("for {}", arch), String::from("end for;")];
+    }
+    let mut lines: Vec<String> = vec![format!("-- configuration of {}.{} generated by hanna", hierarchy.library, hierarchy.unit)];
+    lines.extend(used_libraries.iter().map(|l| format!("library {};", l)));
+    lines.push(String::new());
+    lines.push(format!("configuration {} of {} is", name, entity));
+    lines.extend(block.iter().map(|l| format!("  {}", l)));
+    lines.push(format!("end configuration {};", name));
+    lines
+}
+
+pub fn write_configuration(
+    toplevel: String, libraries_toml_filename: &String, tool_toml_filename: &String,
+    replacements: &HashMap<String, String>, overrides: &BindingOverrides, name: &str, filename: &str,
+) {
+    let (element_list, libraries) = get_single_element_list(&toplevel, libraries_toml_filename, tool_toml_filename, replacements, overrides);
+    let Some(top) = element_list.last() else {
+        eprintln!("No files found!");
+        exit(1);
+    };
+    if top.language != "vhdl" || !top.unit.contains('(') {
+        error!("{} is no VHDL architecture, only these can be configured", toplevel);
+        exit(1)
+    }
+    let hierarchy = get_hierarchy(&element_list);
+    let entity = top.unit.split_once('(').map_or("", |(e, _)| e);
+    let name = if name.is_empty() { format!("cfg_{}", entity) } else { name.to_string() };
+    let filename = if filename.is_empty() { format!("{}.vhd", name) } else { filename.to_string() };
+    let content = get_configuration(&hierarchy, &libraries, &name).join("\n") + "\n";
+    match fs::write(&filename, content) {
+        Ok(_) => println!("{} was written!", filename),
+        Err(err) => {
+            eprintln!("Can't write to '{}'\n{}", filename, err);
+            exit(1);
+        }
+    }
+}
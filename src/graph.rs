@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+use std::process::exit;
+
+use crate::classes::{BindingOverrides, HierarchyNode};
+use crate::tree::get_instance_tree;
+use crate::{get_hierarchy, get_single_element_list};
+
+pub struct Graph {
+    pub name: String,
+    pub nodes: Vec<(String, String)>, // label, style [, unresolved, external]
+    pub edges: Vec<(usize, usize, String)>, // from, to, label
+}
+
+impl Graph {
+    fn new(name: &str) -> Graph {
+        Graph {
+            name: name.to_string(),
+            nodes: Vec::new(),
+            edges: Vec::new(),
+        }
+    }
+
+    fn add_node(&mut self, label: &str, style: &str) -> usize {
+        self.nodes.push((label.to_string(), style.to_string()));
+        self.nodes.len() - 1
+    }
+
+    // node with the label, created if not yet there
+    fn node(&mut self, label: &str, style: &str) -> usize {
+        match self.nodes.iter().position(|(l, _)| l == label) {
+            Some(i) => i,
+            None => self.add_node(label, style),
+        }
+    }
+
+    fn add_edge(&mut self, from: usize, to: usize, label: &str) {
+        let edge = (from, to, label.to_string());
+        if !self.edges.contains(&edge) {
+            self.edges.push(edge);
+        }
+    }
+}
+
+fn node_style(node: &HierarchyNode) -> &str {
+    if node.binding == "unresolved" {
+        "unresolved"
+    } else if node.language == "external" {
+        "external"
+    } else {
+        ""
+    }
+}
+
+// one node per instance
+pub fn get_hierarchy_graph(tree: &HierarchyNode) -> Graph {
+    let mut graph = Graph::new(&format!("{}.{}", tree.library, tree.unit));
+    let top = graph.add_node(&graph.name.clone(), "");
+    add_instances(&mut graph, top, tree);
+    graph
+}
+
+fn add_instances(graph: &mut Graph, parent: usize, node: &HierarchyNode) {
+    for child in &node.children {
+        let id = graph.add_node(&format!("{}.{}", child.library, child.unit), node_style(child));
+        graph.add_edge(parent, id, &child.label);
+        add_instances(graph, id, child);
+    }
+}
+
+// one node per design unit, the edges are labeled with the kind of the dependency
+pub fn get_units_graph(hierarchy: &HierarchyNode) -> Graph {
+    let mut graph = Graph::new(&format!("{}.{}", hierarchy.library, hierarchy.unit));
+    let top = graph.node(&graph.name.clone(), "");
+    add_units(&mut graph, top, hierarchy);
+    graph
+}
+
+fn add_units(graph: &mut Graph, parent: usize, node: &HierarchyNode) {
+    for child in &node.children {
+        let label = format!("{}.{}", child.library, child.unit);
+        let known = graph.nodes.iter().any(|(l, _)| l == &label);
+        let id = graph.node(&label, node_style(child));
+        graph.add_edge(parent, id, &child.kind);
+        if !known {
+            add_units(graph, id, child);
+        }
+    }
+}
+
+// libraries and the libraries they depend on, unknown ones are unresolved
+pub fn get_libraries_graph(name: &str, libraries: &HashMap<String, crate::classes::Library>) -> Graph {
+    let mut graph = Graph::new(name);
+    let mut names: Vec<&String> = libraries.keys().collect();
+    names.sort();
+    for lib_name in names {
+        let lib = &libraries[lib_name];
+        if lib.ignore {
+            continue;
+        }
+        let style = if lib.is_external() { "external" } else { "" };
+        let id = graph.node(lib_name, style);
+        for dep in &lib.depends_on_libs {
+            let dep_id = match libraries.get(dep) {
+                None => graph.node(dep, "unresolved"),
+                Some(l) if l.ignore => continue,
+                Some(l) if l.is_external() => graph.node(dep, "external"),
+                Some(_) => graph.node(dep, ""),
+            };
+            graph.add_edge(id, dep_id, "");
+        }
+    }
+    graph
+}
+
+pub fn render_dot(graph: &Graph) -> String {
+    let mut content: Vec<String> = Vec::new();
+    content.push(format!("digraph \"{}\" {{", graph.name.replace('"', "\\\"")));
+    content.push(String::from("    node [shape=box];"));
+    for (i, (label, style)) in graph.nodes.iter().enumerate() {
+        let attributes = match style.as_str() {
+            "unresolved" => " color=red fontcolor=red style=bold",
+            "external" => " style=dashed",
+            _ => "",
+        };
+        content.push(format!("    n{} [label=\"{}\"{}];", i, label.replace('"', "\\\""), attributes));
+    }
+    for (from, to, label) in &graph.edges {
+        if label.is_empty() {
+            content.push(format!("    n{} -> n{};", from, to));
+        } else {
+            content.push(format!("    n{} -> n{} [label=\"{}\"];", from, to, label.replace('"', "\\\"")));
+        }
+    }
+    content.push(String::from("}"));
+    content.push(String::from(""));
+    content.join("\n")
+}
+
+pub fn render_mermaid(graph: &Graph) -> String {
+    let mut content: Vec<String> = Vec::new();
+    content.push(String::from("graph TD"));
+    for (i, (label, _)) in graph.nodes.iter().enumerate() {
+        content.push(format!("    n{}[\"{}\"]", i, label.replace('"', "#quot;")));
+    }
+    for (from, to, label) in &graph.edges {
+        if label.is_empty() {
+            content.push(format!("    n{} --> n{}", from, to));
+        } else {
+            content.push(format!("    n{} -->|\"{}\"| n{}", from, label.replace('"', "#quot;"), to));
+        }
+    }
+    for style in ["unresolved", "external"] {
+        let ids: Vec<String> = (0..graph.nodes.len())
+            .filter(|i| graph.nodes[*i].1 == style)
+            .map(|i| format!("n{}", i))
+            .collect();
+        if ids.is_empty() {
+            continue;
+        }
+        if style == "unresolved" {
+            content.push(String::from("    classDef unresolved stroke:#d00,color:#d00,stroke-width:2px"));
+        } else {
+            content.push(String::from("    classDef external stroke-dasharray:5 5"));
+        }
+        content.push(format!("    class {} {}", ids.join(","), style));
+    }
+    content.push(String::from(""));
+    content.join("\n")
+}
+
+pub fn print_graph(
+    toplevel: String, libraries_toml_filename: &String, tool_toml_filename: &String,
+    replacements: &HashMap<String, String>, overrides: &BindingOverrides, kind: &str, format: &str,
+) {
+    let (element_list, libraries) = get_single_element_list(&toplevel, libraries_toml_filename, tool_toml_filename, replacements, overrides);
+    if element_list.is_empty() {
+        eprintln!("No files found!");
+        exit(1);
+    }
+    let hierarchy = get_hierarchy(&element_list);
+    let graph = match kind {
+        "units" => get_units_graph(&hierarchy),
+        "libraries" => get_libraries_graph(&toplevel, &libraries),
+        _ => get_hierarchy_graph(&get_instance_tree(&hierarchy)),
+    };
+    if format == "mermaid" {
+        print!("{}", render_mermaid(&graph));
+    } else {
+        print!("{}", render_dot(&graph));
+    }
+}
@@ -3,20 +3,41 @@ use std::collections::HashMap;
 use std::fs::File;
 use std::io::{Read, Write};
 use std::os::unix::fs::PermissionsExt;
-use std::process::{Command, exit};
+use std::path::Path;
+use std::process::exit;
 
-use log::{debug, error, trace, warn};
-use regex::{Captures, Replacer};
+use log::{debug, error, info, trace, warn};
+use regex::{Captures, Regex, Replacer};
 use toml::{Table, Value};
 
 use crate::classes::{
-    ConfigurationInstance, Element, Instance, Library, RE_ARCHITECTURE, RE_COMMENT, RE_ENTITY,
-    RE_ENVVAR, RE_FUNC_PROC, RE_GENERATE, RE_PROCESS, RE_SIGNAL_OR_VARIABLE, RE_STD_LIBS, RE_USAGE,
-    RE_USE_STD_LIBS, ToolLangConfig,
+    BindingOverrides, Component, ConfigurationInstance, Dependency, Element, HierarchyNode, Instance, InterfaceElement, Library, LibraryFiles, ScriptOptions,
+    RE_ARCHITECTURE, RE_ARCHITECTURE_HEADER, RE_COMMENT, RE_COMPONENT, RE_COMPONENT_OWNER, RE_ENTITY, RE_ENVVAR, RE_FUNC_PROC, RE_GENERATE, RE_INTERFACE_LIST,
+    RE_NEXT_ARCHITECTURE, RE_PROCESS, RE_SIGNAL_OR_VARIABLE, RE_STD_LIBS, RE_USAGE,
+    RE_USE_STD_LIBS, ToolCall, ToolLangConfig, ToolStageConfig, ToolTestConfig, RE_ENT,
 };
 use crate::classes::tool_config::ToolConfig;
+use crate::execute::{print_summary, run_tool_calls};
+use crate::stamps::{get_stamps_filename, get_tool_hash, hash_file, read_stamps, write_stamps};
+use crate::tcl::{render_questa_script, render_vivado_script};
 
+pub mod affected;
+pub mod check;
 pub mod classes;
+pub mod execute;
+pub mod gen_config;
+pub mod graph;
+pub mod libmap;
+pub mod roots;
+pub mod makefile;
+pub mod ninja;
+pub mod tcl;
+pub mod test_runner;
+pub mod tree;
+pub mod unused;
+pub mod vhdl_ls;
+pub mod why;
+pub mod stamps;
 
 
 pub fn pre_work_file_content(input: &str) -> String {
@@ -37,15 +58,118 @@ pub fn pre_work_file_content(input: &str) -> String {
         .to_string();
     // strip signal/variables
     ret = RE_SIGNAL_OR_VARIABLE.replace_all(&ret, "").to_string();
-    // strip generates
+    // strip generates and blocks
     ret = RE_GENERATE.replace_all(&ret, "").to_string();
     ret.to_ascii_lowercase()
 }
 
+// statements of an architecture, from its header to the next architecture or the end of the content
+pub fn get_architecture_body<'a>(content: &'a str, entity: &str, arch: &str) -> Option<&'a str> {
+    let start = RE_ARCHITECTURE_HEADER
+        .captures_iter(content)
+        .find(|caps| caps["name"].eq_ignore_ascii_case(arch) && caps["entity"].eq_ignore_ascii_case(entity))?
+        .get(0)?
+        .end();
+    let body = &content[start..];
+    Some(&body[..RE_NEXT_ARCHITECTURE.find(body).map_or(body.len(), |m| m.start())])
+}
+
+// generics or ports of an entity or component declaration, comments have to be stripped
+pub fn get_interface_elements(declaration: &str, kind: &str) -> Vec<InterfaceElement> {
+    let Some(start) = RE_INTERFACE_LIST
+        .captures_iter(declaration)
+        .find(|caps| caps["kind"].eq_ignore_ascii_case(kind))
+        .and_then(|caps| caps.get(0))
+        .map(|m| m.end())
+    else {
+        return Vec::new();
+    };
+    // the list ends at the matching parenthesis
+    let mut depth = 1;
+    let mut end = declaration.len();
+    for (i, c) in declaration[start..].char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    end = start + i;
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    let mut ret: Vec<InterfaceElement> = Vec::new();
+    for item in declaration[start..end].split(';') {
+        // generic types and packages of VHDL-2008 have no colon
+        let Some((names, rest)) = item.split_once(':') else {
+            continue;
+        };
+        let (subtype, default) = rest.split_once(":=").unwrap_or((rest, ""));
+        let mut subtype = subtype.trim();
+        let mut mode = "";
+        if kind == "port" {
+            mode = "in";
+            if let Some((first, remainder)) = subtype.split_once(char::is_whitespace) {
+                if ["in", "out", "inout", "buffer", "linkage"].contains(&first) {
+                    mode = first;
+                    subtype = remainder;
+                }
+            }
+        }
+        let typ = subtype
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join(" ")
+            .replace(" (", "(")
+            .replace("( ", "(")
+            .replace(" )", ")");
+        let default = default.split_whitespace().collect::<Vec<&str>>().join(" ");
+        for name in names.split(',') {
+            // the last word, signal or constant may be in front of it
+            if let Some(name) = name.split_whitespace().last() {
+                ret.push(InterfaceElement::new(name, mode, &typ, &default));
+            }
+        }
+    }
+    ret
+}
+
+// component declarations by the architecture (entity(arch)) or package declaring them
+pub fn get_components(content: &str) -> HashMap<String, HashMap<String, Component>> {
+    let owners: Vec<(usize, String)> = RE_COMPONENT_OWNER
+        .captures_iter(content)
+        .map(|caps| {
+            let owner = match caps.name("package") {
+                Some(package) => package.as_str().to_string(),
+                None => format!("{}({})", &caps["entity"], &caps["arch"]),
+            };
+            (caps.get(0).map_or(0, |m| m.start()), owner)
+        })
+        .collect();
+    let mut ret: HashMap<String, HashMap<String, Component>> = HashMap::new();
+    for caps in RE_COMPONENT.captures_iter(content) {
+        let pos = caps.get(0).map_or(0, |m| m.start());
+        let Some((_, owner)) = owners.iter().rev().find(|(p, _)| *p < pos) else {
+            continue;
+        };
+        let component = Component {
+            name: caps["name"].to_string(),
+            generics: get_interface_elements(&caps["content"], "generic"),
+            ports: get_interface_elements(&caps["content"], "port"),
+        };
+        ret.entry(owner.clone()).or_default().insert(component.name.clone(), component);
+    }
+    ret
+}
+
 pub fn resolve_uses(
     uses: &Vec<String>,
     library: &String,
     libraries: &HashMap<String, Library>,
+    overrides: &BindingOverrides,
+    deps: &mut Vec<Dependency>,
 ) -> Vec<Element> {
     let mut ret = Vec::new();
     for usage in uses {
@@ -70,7 +194,9 @@ pub fn resolve_uses(
                     }
                     Some(lib) => library = lib,
                 };
-                ret.extend(library.resolve(&package, libraries));
+                let resolved = library.resolve(&package, libraries, overrides);
+                add_dependency(deps, &resolved, "use", usage);
+                ret.extend(resolved);
             }
         }
     }
@@ -82,16 +208,34 @@ pub fn resolve_instances(
     instances: &Vec<Instance>,
     library: &String,
     libraries: &HashMap<String, Library>,
+    overrides: &BindingOverrides,
     configuration_instances: &HashMap<String, ConfigurationInstance>,
     uses: &Vec<String>,
+    deps: &mut Vec<Dependency>,
 ) -> Vec<Element> {
     let mut ret = Vec::new();
     for instance in instances {
-        ret.extend(instance.resolve(library, libraries, configuration_instances, uses));
+        ret.extend(instance.resolve(library, libraries, overrides, configuration_instances, uses, deps));
     }
     ret
 }
 
+// the resolved unit itself is the last element of a resolved list
+pub fn add_dependency(deps: &mut Vec<Dependency>, resolved: &[Element], kind: &str, label: &str) {
+    if let Some(el) = resolved.last() {
+        deps.push(el.as_dependency(kind, label));
+    }
+}
+
+// dependency of an instance, together with the way it was bound
+pub fn add_instance_dependency(deps: &mut Vec<Dependency>, resolved: &[Element], label: &str, binding: &str) {
+    if let Some(el) = resolved.last() {
+        let mut dep = el.as_dependency("instance", label);
+        dep.binding = binding.to_string();
+        deps.push(dep);
+    }
+}
+
 pub fn rework_file_path(path: String) -> String {
     RE_ENVVAR
         .replace_all(path.as_str(), EnvReplacer)
@@ -168,9 +312,23 @@ pub fn read_libraries_toml(
     let config = read_toml(filename, &replacements_all);
     for (name, value) in config {
         let ignore: bool;
+        let mut precompiled = String::new();
         let mut vhdl_scope: Vec<String> = Vec::new();
         let mut verilog_scope: Vec<String> = Vec::new();
         match value {
+            Value::Array(l) if name == "external_libraries" => {
+                for e in l {
+                    let Some(lib_name) = e.as_str() else {
+                        error!("TOML libraries: 'external_libraries' must be a list of strings!");
+                        exit(1)
+                    };
+                    let mut lib = Library::new();
+                    lib.name = lib_name.to_string();
+                    lib.external = true;
+                    ret.insert(lib.name.clone(), lib);
+                }
+                continue;
+            }
             Value::Table(t) => {
                 if let Some(v) = t.get("ignore") {
                     match v {
@@ -183,6 +341,15 @@ pub fn read_libraries_toml(
                 } else {
                     ignore = false
                 }
+                if let Some(v) = t.get("precompiled") {
+                    match v {
+                        Value::String(s) => precompiled = s.clone(),
+                        _ => {
+                            error!("TOML libraries: 'precompiled' must be a string!");
+                            exit(1)
+                        }
+                    }
+                }
                 if let Some(v) = t.get("vhdl") {
                     match v {
                         Value::Array(l) => {
@@ -225,6 +392,8 @@ pub fn read_libraries_toml(
             modules: HashMap::new(),
             depends_on_libs: Vec::new(),
             ignore,
+            external: false,
+            precompiled,
             vhdl_scope,
             verilog_scope,
             all_vhdl_elements: Vec::new(),
@@ -245,6 +414,7 @@ pub fn get_library_names_from_toml(
     for (name, value) in config {
         let ignore: bool;
         match value {
+            Value::Array(_) if name == "external_libraries" => continue,
             Value::Table(t) => {
                 if let Some(v) = t.get("ignore") {
                     match v {
@@ -424,14 +594,63 @@ pub fn read_tool_toml(filename: &String, replacements: &HashMap<String, String>)
         }
     }
 
+    let mut elaborate_cfg = ToolStageConfig::new();
+    let mut run_cfg = ToolStageConfig::new();
+    for stage in ["elaborate", "run"] {
+        let cfg: ToolStageConfig;
+        if let Some(c) = config.get(stage) {
+            match c {
+                Value::Table(t) => {
+                    cfg = get_tool_stage_config(filename, &stage.to_string(), t);
+                }
+                _ => {
+                    error!(
+                            "{}: {}: value must be a HashMap with an entry for commands",
+                            filename,stage
+                        );
+                    exit(1);
+                }
+            }
+        } else {
+            cfg = ToolStageConfig::new()
+        }
+        if stage == "elaborate" {
+            elaborate_cfg = cfg;
+        } else {
+            run_cfg = cfg
+        }
+    }
+
+    let test_cfg = match config.get("test") {
+        None => ToolTestConfig::new(),
+        Some(Value::Table(t)) => get_tool_test_config(filename, t),
+        Some(_) => {
+            error!("{}: test: value must be a HashMap", filename);
+            exit(1);
+        }
+    };
+
     ToolConfig {
         common,
         vhdl: vhdl_cfg,
         verilog: verilog_cfg,
+        elaborate: elaborate_cfg,
+        run: run_cfg,
+        test: test_cfg,
         exec_before,
         exec_after,
         exec_per_lib,
         replacement: replace,
+        generic_format: match config.get("generic_format") {
+            None => String::from("-g{name}={value}"),
+            Some(Value::String(f)) => f.clone(),
+            Some(_) => {
+                error!("{}: generic_format value must be a String!", filename);
+                exit(1);
+            }
+        },
+        cwd: get_cwd_value(filename, &String::from("top level"), &config),
+        env: get_env_value(filename, &String::from("top level"), &config),
     }
 }
 
@@ -513,32 +732,378 @@ pub fn get_tool_lang_config(filename: &String, lang: &String, table: &Table) ->
     }
 }
 
-pub fn get_element_list(lib_name: String, toplevel: String, libraries_toml_filename: &String, tool_toml_filename: &String,
-                        replacements: &HashMap<String, String>, ) -> (Vec<Element>, HashMap<String, Library>) {
-    let tool_config = read_tool_toml(tool_toml_filename, &replacements);
+pub fn get_tool_stage_config(filename: &String, stage: &String, table: &Table) -> ToolStageConfig {
+    let mut commands: Vec<String> = Vec::new();
+    if let Some(e) = table.get("commands") {
+        match e {
+            Value::Array(va) => {
+                for e in va {
+                    commands.push(String::from(e.as_str().unwrap_or("")));
+                }
+            }
+            _ => {
+                error!(
+                    "{}: commands value in [{}] must be an array of String!",
+                    filename, stage
+                );
+                exit(1);
+            }
+        }
+    }
+
+    let cwd = get_cwd_value(filename, stage, table);
+    let env = get_env_value(filename, stage, table);
+
+    ToolStageConfig { commands, cwd, env }
+}
+
+pub fn get_tool_test_config(filename: &String, table: &Table) -> ToolTestConfig {
+    let mut cfg = ToolTestConfig::new();
+    for (key, patterns) in [("fail_patterns", &mut cfg.fail_patterns), ("pass_patterns", &mut cfg.pass_patterns)] {
+        match table.get(key) {
+            None => {}
+            Some(Value::Array(va)) => {
+                for e in va {
+                    let pattern = String::from(e.as_str().unwrap_or(""));
+                    if let Err(err) = Regex::new(&pattern) {
+                        error!("{}: {} in [test]: {}", filename, key, err);
+                        exit(1);
+                    }
+                    patterns.push(pattern);
+                }
+            }
+            Some(_) => {
+                error!("{}: {} value in [test] must be an array of String!", filename, key);
+                exit(1);
+            }
+        }
+    }
+    match table.get("timeout") {
+        None => {}
+        Some(Value::Integer(t)) if *t >= 0 => cfg.timeout = *t as u64,
+        Some(_) => {
+            error!("{}: timeout value in [test] must be a number of seconds!", filename);
+            exit(1);
+        }
+    }
+    match table.get("run_dir") {
+        None => {}
+        Some(Value::String(d)) => cfg.run_dir = d.clone(),
+        Some(_) => {
+            error!("{}: run_dir value in [test] must be a String!", filename);
+            exit(1);
+        }
+    }
+    // testbench = { NAME = value } or testbench = [{ NAME = value }, ...], one run per table
+    match table.get("generics") {
+        None => {}
+        Some(Value::Table(t)) => {
+            for (pattern, value) in t {
+                let tables: Vec<&Value> = match value {
+                    Value::Array(va) => va.iter().collect(),
+                    _ => vec![value],
+                };
+                for table in tables {
+                    let Value::Table(generics) = table else {
+                        error!("{}: generics of {} in [test] must be a table or an array of tables!", filename, pattern);
+                        exit(1);
+                    };
+                    let generics = generics
+                        .iter()
+                        .map(|(name, value)| match value {
+                            Value::String(s) => (name.clone(), s.clone()),
+                            _ => (name.clone(), value.to_string()),
+                        })
+                        .collect();
+                    cfg.generics.push((pattern.clone(), generics));
+                }
+            }
+        }
+        Some(_) => {
+            error!("{}: generics value in [test] must be a table of testbenches!", filename);
+            exit(1);
+        }
+    }
+    cfg
+}
+
+pub fn get_cwd_value(filename: &String, section: &String, table: &Table) -> String {
+    match table.get("cwd") {
+        None => String::new(),
+        Some(Value::String(cwd)) => cwd.clone(),
+        Some(_) => {
+            error!("{}: cwd value in [{}] must be a String!", filename, section);
+            exit(1);
+        }
+    }
+}
+
+pub fn get_env_value(filename: &String, section: &String, table: &Table) -> HashMap<String, String> {
+    let mut ret: HashMap<String, String> = HashMap::new();
+    match table.get("env") {
+        None => {}
+        Some(Value::Table(t)) => {
+            for (key, value) in t {
+                ret.insert(key.clone(), String::from(value.as_str().unwrap_or("")));
+            }
+        }
+        Some(_) => {
+            error!(
+                "{}: env value in [{}] must be a HashMap of type 'NAME = value'!",
+                filename, section
+            );
+            exit(1);
+        }
+    }
+    ret
+}
+
+// generics for the {generics} placeholder, each rendered with the generic_format of the tool
+pub fn render_generics(generic_format: &str, generics: &[(String, String)]) -> String {
+    generics
+        .iter()
+        .map(|(name, value)| generic_format.replace("{name}", name).replace("{value}", value))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+// generics with the values of overrides, names are case insensitive as in VHDL
+pub fn merge_generics(generics: &[(String, String)], overrides: &[(String, String)]) -> Vec<(String, String)> {
+    let mut ret: Vec<(String, String)> = generics.to_vec();
+    for (name, value) in overrides {
+        match ret.iter_mut().find(|(n, _)| n.eq_ignore_ascii_case(name)) {
+            Some(generic) => generic.1 = value.clone(),
+            None => ret.push((name.clone(), value.clone())),
+        }
+    }
+    ret
+}
+
+// replaces the toplevel placeholders {toplevel}, {top_library}, {top} and {top_arch}.
+pub fn replace_top(entry: &str, toplevel: &str) -> String {
+    let (mut top_library, mut top, mut top_arch) = (String::new(), String::new(), String::new());
+    if let Some(caps) = RE_ENT.captures(toplevel) {
+        top_library = caps.name("lib").map_or("", |m| m.as_str()).to_string();
+        top = caps.name("entity").map_or("", |m| m.as_str()).to_string();
+        top_arch = caps.name("arch").map_or("", |m| m.as_str()).to_string();
+    }
+    entry
+        .replace("{toplevel}", toplevel)
+        .replace("{top_library}", &top_library)
+        .replace("{top_arch}", &top_arch)
+        .replace("{top}", &top)
+}
+
+// library of a toplevel, lib_name if it has none
+fn get_top_library(top: &str, lib_name: &str) -> String {
+    RE_ENT
+        .captures(top)
+        .and_then(|caps| caps.name("lib").map(|m| m.as_str().to_string()))
+        .unwrap_or(lib_name.to_string())
+}
+
+// toplevels can contain glob patterns, without a toplevel all libraries are compiled completely
+pub fn get_element_list(lib_name: String, toplevels: &[String], libraries_toml_filename: &String, tool_toml_filename: &String,
+                        replacements: &HashMap<String, String>, overrides: &BindingOverrides) -> (Vec<Element>, HashMap<String, Library>) {
+    let libs = get_libraries(libraries_toml_filename, tool_toml_filename, replacements);
+    if toplevels.iter().all(|t| t.is_empty()) {
+        return (get_all_elements(&libs), libs);
+    }
+    let mut lists: Vec<(String, Vec<Element>)> = Vec::new();
+    for top in expand_toplevels(&libs, &lib_name, toplevels) {
+        let element_list = get_toplevel_elements(&libs, &get_top_library(&top, &lib_name), &top, overrides);
+        lists.push((top, element_list));
+    }
+    if lists.len() == 1 {
+        return (lists.pop().map(|(_, element_list)| element_list).unwrap_or_default(), libs);
+    }
+    let conflicts = get_architecture_conflicts(&lists);
+    if !conflicts.is_empty() {
+        error!("The merged compile order changes the default architecture of:\n    {}", conflicts.join("\n    "));
+        exit(1)
+    }
+    // each list is in compile order, so is the concatenation of them
+    (lists.into_iter().flat_map(|(_, element_list)| element_list).collect(), libs)
+}
+
+// for commands working on one toplevel, a glob pattern has to match exactly one
+pub fn get_single_element_list(toplevel: &str, libraries_toml_filename: &String, tool_toml_filename: &String,
+                               replacements: &HashMap<String, String>, overrides: &BindingOverrides) -> (Vec<Element>, HashMap<String, Library>) {
+    let libs = get_libraries(libraries_toml_filename, tool_toml_filename, replacements);
+    let lib_name = get_top_library(toplevel, "");
+    let toplevels = expand_toplevels(&libs, &lib_name, &[toplevel.to_string()]);
+    let [top] = toplevels.as_slice() else {
+        error!("Exactly one toplevel is needed, {} gives {}", toplevel, toplevels.len());
+        exit(1)
+    };
+    let element_list = get_toplevel_elements(&libs, &get_top_library(top, &lib_name), top, overrides);
+    (element_list, libs)
+}
+
+// toplevels can be comma separated, glob patterns are matched against the designs and configurations of their library
+pub fn expand_toplevels(libs: &HashMap<String, Library>, lib_name: &str, toplevels: &[String]) -> Vec<String> {
+    let mut ret: Vec<String> = Vec::new();
+    for top in toplevels.iter().flat_map(|t| t.split(',')).map(|t| t.trim()).filter(|t| !t.is_empty()) {
+        if !top.contains(['*', '?', '[']) {
+            if !ret.contains(&top.to_string()) {
+                ret.push(top.to_string());
+            }
+            continue;
+        }
+        let (top_lib, pattern) = top.split_once('.').unwrap_or((lib_name, top));
+        let Ok(pattern) = glob::Pattern::new(pattern) else {
+            error!("No valid pattern: {}", top);
+            exit(1)
+        };
+        let Some(lib) = libs.get(top_lib) else {
+            error!("A lib with name {} is not defined!", top_lib);
+            exit(1)
+        };
+        let matches: Vec<String> = lib
+            .list_designs()
+            .into_iter()
+            .filter(|name| pattern.matches(name))
+            .map(|name| format!("{}.{}", top_lib, name))
+            .collect();
+        if matches.is_empty() {
+            error!("No toplevel matches {}", top);
+            exit(1)
+        }
+        for name in matches {
+            if !ret.contains(&name) {
+                ret.push(name);
+            }
+        }
+    }
+    ret
+}
+
+// architectures of each entity in the order they are analyzed first, the last one is bound by default
+fn get_analyzed_architectures<'a>(elements: impl Iterator<Item = &'a Element>) -> HashMap<String, Vec<String>> {
+    let mut ret: HashMap<String, Vec<String>> = HashMap::new();
+    for el in elements {
+        if let Some((entity, arch)) = el.unit.strip_suffix(')').and_then(|u| u.split_once('(')) {
+            let archs = ret.entry(format!("{}.{}", el.library, entity)).or_default();
+            if !archs.contains(&arch.to_string()) {
+                archs.push(arch.to_string());
+            }
+        }
+    }
+    ret
+}
+
+// entities with an instance bound without a configuration or an architecture in the source,
+// the tool binds them to the last analyzed architecture
+fn get_default_bound_entities(element_list: &[Element]) -> Vec<String> {
+    let mut ret: Vec<String> = Vec::new();
+    for dep in element_list.iter().flat_map(|el| el.depends_on.iter()) {
+        if dep.kind != "instance" || (dep.binding != "default" && dep.binding != "override") {
+            continue;
+        }
+        if let Some((entity, _)) = dep.unit.split_once('(') {
+            let entity = format!("{}.{}", dep.library, entity);
+            if !ret.contains(&entity) {
+                ret.push(entity);
+            }
+        }
+    }
+    ret
+}
+
+// entities whose last analyzed architecture in the merged compile order differs from the one
+// of a toplevel binding them by default, the binding of that toplevel would change
+pub fn get_architecture_conflicts(lists: &[(String, Vec<Element>)]) -> Vec<String> {
+    let merged = get_analyzed_architectures(lists.iter().flat_map(|(_, element_list)| element_list.iter()));
+    let mut ret: Vec<String> = Vec::new();
+    for (entity, archs) in &merged {
+        let Some(last) = archs.last() else {
+            continue;
+        };
+        let mut details: Vec<String> = Vec::new();
+        let mut changed = false;
+        for (top, element_list) in lists {
+            // a configuration or an explicit architecture doesn't depend on the order
+            if !get_default_bound_entities(element_list).contains(entity) {
+                continue;
+            }
+            if let Some(own) = get_analyzed_architectures(element_list.iter()).get(entity).and_then(|a| a.last().cloned()) {
+                changed |= &own != last;
+                details.push(format!("{}: {}", top, own));
+            }
+        }
+        if changed {
+            ret.push(format!("{} ({}; analyzed last: {})", entity, details.join("; "), last));
+        }
+    }
+    ret.sort();
+    ret
+}
+
+// analyzed libraries of libraries.toml, with the replacements of tool.toml
+pub fn get_libraries(libraries_toml_filename: &String, tool_toml_filename: &String,
+                     replacements: &HashMap<String, String>) -> HashMap<String, Library> {
+    let tool_config = read_tool_toml(tool_toml_filename, replacements);
     let mut replacements_all = replacements.clone();
     for (key, value) in &tool_config.replacement {
         replacements_all.insert(key.clone(), value.clone());
     }
-    let libs = read_libraries_toml(
-        &libraries_toml_filename,
+    read_libraries_toml(
+        libraries_toml_filename,
         &replacements_all,
         &tool_config,
-    );
-    match libs.get(&lib_name) {
+    )
+}
+
+pub fn get_toplevel_elements(libs: &HashMap<String, Library>, lib_name: &String, toplevel: &String, overrides: &BindingOverrides) -> Vec<Element> {
+    match libs.get(lib_name) {
         None => {
             error!("A lib with name {} is not defined!",lib_name);
             exit(1)
         }
-        Some(lib) => { (lib.resolve(&toplevel, &libs), libs) }
+        Some(lib) => {
+            // units of precompiled and vendor libraries are not compiled
+            let mut element_list = lib.resolve(toplevel, libs, overrides);
+            element_list.retain(|el| el.language != "external");
+            element_list
+        }
+    }
+}
+
+// every unit of every compiled library, in the order the units depend on each other
+pub fn get_all_elements(libs: &HashMap<String, Library>) -> Vec<Element> {
+    let mut element_list: Vec<Element> = Vec::new();
+    for lib_name in get_sorted_libraries(libs) {
+        let Some(lib) = libs.get(&lib_name) else {
+            continue;
+        };
+        if !lib.is_compiled() {
+            continue;
+        }
+        let mut units: Vec<String> = lib.packages.keys().cloned().collect();
+        units.sort();
+        units.extend(lib.list_designs());
+        let mut modules: Vec<&String> = lib.modules.keys().collect();
+        modules.sort();
+        units.extend(modules.into_iter().filter(|m| !lib.designs.contains_key(*m)).cloned());
+        for unit in units {
+            element_list.extend(lib.resolve(&unit, libs, &BindingOverrides::new()));
+        }
+        // files without any unit hanna knows of are compiled at the end of their library
+        for el in lib.all_vhdl_elements.iter().chain(lib.all_verilog_elements.iter()) {
+            if !element_list.iter().any(|e| e.filename == el.filename) {
+                element_list.push(el.copy());
+            }
+        }
     }
+    element_list.retain(|el| el.language != "external");
+    element_list
 }
 
 pub fn write_lib_lists(
-    lib_name: String, toplevel: String, libraries_toml_filename: &String, tool_toml_filename: &String,
-    replacements: &HashMap<String, String>, filename: &String,
+    lib_name: String, toplevels: &[String], libraries_toml_filename: &String, tool_toml_filename: &String,
+    replacements: &HashMap<String, String>, overrides: &BindingOverrides, filename: &String,
 ) {
-    let (element_list, libraries) = get_element_list(lib_name, toplevel, libraries_toml_filename, tool_toml_filename, replacements);
+    let (element_list, libraries) = get_element_list(lib_name, toplevels, libraries_toml_filename, tool_toml_filename, replacements, overrides);
     let lib_order = get_sorted_libraries(&libraries);
     let mut l_path = String::from(filename.strip_suffix('/').unwrap_or(&*filename));
     l_path.push('/');
@@ -582,32 +1147,75 @@ pub fn write_lib_lists(
     }
 }
 
+pub const JSON_VERSION: u32 = 1;
+
 pub fn write_json_file(
-    lib_name: String, toplevel: String, libraries_toml_filename: &String, tool_toml_filename: &String,
-    replacements: &HashMap<String, String>, filename: &String,
+    lib_name: String, toplevels: &[String], libraries_toml_filename: &String, tool_toml_filename: &String,
+    replacements: &HashMap<String, String>, overrides: &BindingOverrides, filename: &String,
 ) {
-    let (element_list, libraries) = get_element_list(lib_name, toplevel, libraries_toml_filename, tool_toml_filename, replacements);
+    let (element_list, libraries) = get_element_list(lib_name.clone(), toplevels, libraries_toml_filename, tool_toml_filename, replacements, overrides);
     let lib_order = get_sorted_libraries(&libraries);
-    let mut l_path = String::from(filename.strip_suffix('/').unwrap_or(&*filename));
-    l_path.push('/');
-    let mut res: HashMap<String, Vec<String>> = HashMap::new();
-    match element_list.last() {
-        None => {
-            eprintln!("No files found to write out!");
-            exit(1);
-        }
-        Some(last_el) => last_el,
-    };
+    if element_list.is_empty() {
+        eprintln!("No files found to write out!");
+        exit(1);
+    }
+    let compile_order = get_compile_order(&element_list, &lib_order);
+    let mut libs: Vec<serde_json::Value> = Vec::new();
     for lib_name in &lib_order {
-        let mut lib_list: Vec<String> = Vec::new();
-        for el in &element_list {
-            if el.library.eq(lib_name) && !lib_list.contains(&el.filename) {
-                lib_list.push(el.filename.clone())
+        let files: Vec<&String> = compile_order
+            .iter()
+            .filter(|u| &u.library == lib_name)
+            .flat_map(|u| u.files.iter())
+            .collect();
+        if !files.is_empty() {
+            libs.push(serde_json::json!({"name": lib_name, "files": files}));
+        }
+    }
+    let mut files: Vec<serde_json::Value> = Vec::new();
+    for (order, filename) in compile_order.iter().flat_map(|u| u.files.iter()).enumerate() {
+        let elements: Vec<&Element> = element_list.iter().filter(|el| &el.filename == filename).collect();
+        let mut units: Vec<&String> = Vec::new();
+        let mut depends_on: Vec<serde_json::Value> = Vec::new();
+        for el in &elements {
+            if !units.contains(&&el.unit) {
+                units.push(&el.unit);
             }
         }
-        res.insert(lib_name.to_string(), lib_list);
+        for el in &elements {
+            for dep in &el.depends_on {
+                let value = serde_json::json!({
+                    "library": dep.library,
+                    "unit": dep.unit,
+                    "kind": dep.kind,
+                    "label": dep.label,
+                });
+                if !units.contains(&&dep.unit) && !depends_on.contains(&value) {
+                    depends_on.push(value);
+                }
+            }
+        }
+        files.push(serde_json::json!({
+            "filename": filename,
+            "library": elements[0].library,
+            "language": elements[0].language,
+            "order": order,
+            "units": units,
+            "depends_on": depends_on,
+        }));
     }
-    res.insert("libraries".to_string(), lib_order);
+    // one hierarchy per toplevel, none without a toplevel
+    let toplevels = expand_toplevels(&libraries, &lib_name, toplevels);
+    let hierarchies: Vec<serde_json::Value> = toplevels
+        .iter()
+        .map(|top| get_hierarchy(&get_toplevel_elements(&libraries, &get_top_library(top, &lib_name), top, overrides)).to_json())
+        .collect();
+    let res = serde_json::json!({
+        "version": JSON_VERSION,
+        "toplevels": toplevels,
+        "libraries": libs,
+        "files": files,
+        "hierarchies": hierarchies,
+    });
     let content = serde_json::to_string_pretty(&res).unwrap_or(String::from(""));
     // ToDo: Create directory if it does not exist!
     let mut file = match File::create(filename) {
@@ -620,30 +1228,128 @@ pub fn write_json_file(
     let _ = file.write(content.as_ref());
 }
 
-pub fn gen_script(
-    lib_name: String, toplevel: String, libraries_toml_filename: &String, tool_toml_filename: &String,
-    replacements: &HashMap<String, String>, filename: &String,
-) {
-    let (element_list, libraries) = get_element_list(lib_name, toplevel, libraries_toml_filename, tool_toml_filename, replacements);
-    let lib_order = get_sorted_libraries(&libraries);
-    let tool_config = read_tool_toml(tool_toml_filename, &replacements);
-    let mut l_path = String::from(filename.strip_suffix('/').unwrap_or(&*filename));
-    l_path.push('/');
+// resolved hierarchy below the toplevel, which is the last element of the list
+pub fn get_hierarchy(element_list: &[Element]) -> HierarchyNode {
+    let mut elements: HashMap<(&str, &str), &Element> = HashMap::new();
+    for el in element_list {
+        elements.entry((el.library.as_str(), el.unit.as_str())).or_insert(el);
+    }
+    let Some(top) = element_list.last() else {
+        return HierarchyNode::new("", "", "", "toplevel", "");
+    };
+    let mut node = HierarchyNode::new(&top.library, &top.unit, &top.filename, "toplevel", "");
+    node.language = top.language.clone();
+    add_hierarchy_children(&mut node, &elements, &mut Vec::new());
+    node
+}
 
-    match element_list.last() {
-        None => {
-            eprintln!("No files found to write out!");
-            exit(1);
-        }
-        Some(last_el) => last_el,
+fn add_hierarchy_children(node: &mut HierarchyNode, elements: &HashMap<(&str, &str), &Element>, path: &mut Vec<String>) {
+    let key = format!("{}.{}", node.library, node.unit);
+    // a unit can't contain itself
+    if path.contains(&key) {
+        return;
+    }
+    let Some(el) = elements.get(&(node.library.as_str(), node.unit.as_str())) else {
+        return;
     };
-    // get lists per language
+    path.push(key);
+    for dep in &el.depends_on {
+        let mut child = HierarchyNode::new(&dep.library, &dep.unit, &dep.filename, &dep.kind, &dep.label);
+        child.binding = dep.binding.clone();
+        // units sharing a file with another one may not have an element of their own
+        child.language = match elements
+            .get(&(dep.library.as_str(), dep.unit.as_str()))
+            .or_else(|| elements.values().find(|e| !dep.filename.is_empty() && e.filename == dep.filename))
+        {
+            Some(dep_el) => dep_el.language.clone(),
+            None if dep.binding == "unresolved" => String::new(),
+            None => String::from("external"),
+        };
+        add_hierarchy_children(&mut child, elements, path);
+        node.children.push(child);
+    }
+    path.pop();
+}
+
+pub const JSON_SCHEMA: &str = r##"{
+  "$schema": "https://json-schema.org/draft/2020-12/schema",
+  "title": "hanna design export",
+  "type": "object",
+  "required": ["version", "toplevels", "libraries", "files", "hierarchies"],
+  "properties": {
+    "version": {"const": 1},
+    "toplevels": {"type": "array", "items": {"type": "string"}},
+    "libraries": {
+      "description": "libraries in compile order",
+      "type": "array",
+      "items": {
+        "type": "object",
+        "required": ["name", "files"],
+        "properties": {
+          "name": {"type": "string"},
+          "files": {"type": "array", "items": {"type": "string"}}
+        }
+      }
+    },
+    "files": {
+      "description": "files in compile order",
+      "type": "array",
+      "items": {
+        "type": "object",
+        "required": ["filename", "library", "language", "order", "units", "depends_on"],
+        "properties": {
+          "filename": {"type": "string"},
+          "library": {"type": "string"},
+          "language": {"enum": ["vhdl", "verilog"]},
+          "order": {"description": "position in the global compile order", "type": "integer"},
+          "units": {"description": "units defined in the file", "type": "array", "items": {"type": "string"}},
+          "depends_on": {"type": "array", "items": {"$ref": "#/$defs/dependency"}}
+        }
+      }
+    },
+    "hierarchies": {"description": "hierarchy of each toplevel", "type": "array", "items": {"$ref": "#/$defs/node"}}
+  },
+  "$defs": {
+    "dependency": {
+      "type": "object",
+      "required": ["library", "unit", "kind", "label"],
+      "properties": {
+        "library": {"type": "string"},
+        "unit": {"type": "string"},
+        "kind": {"enum": ["use", "entity", "architecture", "instance", "package"]},
+        "label": {"description": "instance label or use clause", "type": "string"}
+      }
+    },
+    "node": {
+      "type": "object",
+      "required": ["library", "unit", "filename", "kind", "label", "language", "binding", "children"],
+      "properties": {
+        "library": {"type": "string"},
+        "unit": {"type": "string"},
+        "filename": {"description": "empty for precompiled and external libraries", "type": "string"},
+        "kind": {"enum": ["toplevel", "use", "entity", "architecture", "instance", "package"]},
+        "label": {"type": "string"},
+        "language": {"description": "empty if unresolved", "enum": ["", "vhdl", "verilog", "external"]},
+        "binding": {"description": "how an instance was bound, empty for other kinds", "enum": ["", "configuration", "entity", "default", "verilog", "external", "unresolved", "override"]},
+        "children": {"type": "array", "items": {"$ref": "#/$defs/node"}}
+      }
+    }
+  }
+}"##;
+
+pub fn print_json_schema() {
+    println!("{}", JSON_SCHEMA);
+}
+
+pub fn get_file_lists(
+    element_list: &Vec<Element>, lib_order: &Vec<String>,
+) -> (HashMap<String, Vec<String>>, HashMap<String, Vec<String>>) {
     let mut file_lists_verilog: HashMap<String, Vec<String>> = HashMap::new();
     let mut file_lists_vhdl: HashMap<String, Vec<String>> = HashMap::new();
-    for lib_name in &lib_order {
+    for lib_name in lib_order {
         let mut verilog_list: Vec<String> = Vec::new();
         let mut vhdl_list: Vec<String> = Vec::new();
-        for el in &element_list {
+        for el in element_list {
             if el.library.eq(lib_name) {
                 if el.language.eq("vhdl") {
                     if !vhdl_list.contains(&el.filename) {
@@ -657,166 +1363,443 @@ pub fn gen_script(
         file_lists_verilog.insert(lib_name.to_string(), verilog_list);
         file_lists_vhdl.insert(lib_name.to_string(), vhdl_list);
     }
+    (file_lists_verilog, file_lists_vhdl)
+}
 
-    let mut content: Vec<String> = Vec::new();
-    content.push(String::from("#!/usr/bin/env sh"));
-    // exec_before
-    for entry in &tool_config.exec_before {
-        content.push(entry.clone());
+// files per library and language in compile order, Verilog first
+pub fn get_compile_order(element_list: &Vec<Element>, lib_order: &Vec<String>) -> Vec<LibraryFiles> {
+    let (file_lists_verilog, file_lists_vhdl) = get_file_lists(element_list, lib_order);
+    let mut ret: Vec<LibraryFiles> = Vec::new();
+    for (lang, file_lists) in [("verilog", &file_lists_verilog), ("vhdl", &file_lists_vhdl)] {
+        for lib_name in lib_order {
+            match file_lists.get(lib_name) {
+                Some(files) if !files.is_empty() => {
+                    let mut unit = LibraryFiles::new(lib_name, lang);
+                    unit.files = files.clone();
+                    ret.push(unit);
+                }
+                _ => {}
+            }
+        }
     }
-    // exec_per_lib
-    for lib_name in &lib_order {
-        for entry in &tool_config.exec_per_lib {
-            content.push(entry.replace("{library}", lib_name));
-        }
-        for entry in &tool_config.vhdl.exec_per_lib {
-            content.push(entry.replace("{library}", lib_name));
+    ret
+}
+
+fn shell_call(stage: &str, library: &str, entry: String, tool_config: &ToolConfig) -> ToolCall {
+    let mut call = ToolCall::new(stage, library, "");
+    call.fragments.push(entry);
+    call.shell = true;
+    call.cwd = tool_config.cwd.clone();
+    call.env = tool_config.env.clone();
+    call
+}
+
+// fragments with {library} and {files} replaced, a standalone {files} gives one fragment per file
+fn expand_fragments(values: &[String], lib_name: &str, files: &[String]) -> Vec<String> {
+    let mut ret: Vec<String> = Vec::new();
+    for value in values {
+        if value == "{files}" {
+            ret.extend(files.iter().cloned());
+        } else {
+            ret.push(value.replace("{library}", lib_name).replace("{files}", &files.join(" ")));
         }
-        for entry in &tool_config.verilog.exec_per_lib {
-            content.push(entry.replace("{library}", lib_name));
+    }
+    ret
+}
+
+pub fn get_tool_calls(
+    element_list: &Vec<Element>, lib_order: &Vec<String>, tool_config: &ToolConfig,
+    toplevels: &[String], stop_after: &String, generics: &[(String, String)],
+) -> Vec<ToolCall> {
+    let compile_order = get_compile_order(element_list, lib_order);
+    let mut ret: Vec<ToolCall> = Vec::new();
+    // exec_before
+    for entry in &tool_config.exec_before {
+        ret.push(shell_call("before", "", entry.clone(), tool_config));
+    }
+    // exec_per_lib
+    for lib_name in lib_order {
+        for entry in tool_config.exec_per_lib.iter()
+            .chain(tool_config.vhdl.exec_per_lib.iter())
+            .chain(tool_config.verilog.exec_per_lib.iter()) {
+            ret.push(shell_call("per_lib", lib_name, entry.replace("{library}", lib_name), tool_config));
         }
     }
-    content.push(String::from(""));
 
     // compile verilog
     // compile vhdl
     for lang in ["verilog", "vhdl"] {
+        let lang_config = if lang == "vhdl" {
+            &tool_config.vhdl
+        } else {
+            &tool_config.verilog
+        };
+        let tmp: Vec<String> = vec![String::from("")];
+        let lang_commons = if lang_config.common.is_empty() {
+            &tmp
+        } else {
+            &lang_config.common
+        };
         for main_common in &tool_config.common {
-            let single_call: &bool;
-            let lang_commons: &Vec<String>;
-            let per_lib: &Vec<String>;
-            let tmp: Vec<String> = vec![String::from("")];
-            if lang == "vhdl" {
-                single_call = &tool_config.vhdl.single_call;
-                if !tool_config.vhdl.common.is_empty() {
-                    lang_commons = &tool_config.vhdl.common;
-                } else {
-                    lang_commons = &tmp;
-                }
-                per_lib = &tool_config.vhdl.per_lib;
-            } else {
-                single_call = &tool_config.verilog.single_call;
-                if !tool_config.verilog.common.is_empty() {
-                    lang_commons = &tool_config.verilog.common;
-                } else {
-                    lang_commons = &tmp;
-                }
-                per_lib = &tool_config.verilog.per_lib;
-            }
-
             for lang_common in lang_commons {
-                let mut call: Vec<String>;
-                let mut common: Vec<String> = Vec::new();
-                common.push(main_common.clone());
-                if !main_common.is_empty() {
-                    common.push(String::from(" \\\n    "))
-                }
-                common.push(lang_common.clone());
-                if !lang_common.is_empty() {
-                    if *single_call {
-                        common.push(String::from(" \\"))
+                let mut single = ToolCall::new("compile", "", lang);
+                let mut single_libs: Vec<String> = Vec::new();
+                for unit in compile_order.iter().filter(|u| u.language == lang) {
+                    if lang_config.single_call {
+                        single.fragments.extend(expand_fragments(&lang_config.per_lib, &unit.library, &unit.files));
+                        single.files.extend(unit.files.iter().cloned());
+                        single_libs.push(unit.library.clone());
                     } else {
-                        common.push(String::from(" \\\n    "))
+                        ret.push(compile_call(tool_config, lang, main_common, lang_common, &unit.library, &unit.files));
                     }
                 }
-                //
-                if *single_call {
-                    content.push(common.join(""));
-                }
-                for lib_name in &lib_order {
-                    let files_empty: Vec<String> = Vec::new();
-                    let files = if lang == "vhdl" {
-                        file_lists_vhdl.get(lib_name).unwrap_or(&files_empty)
-                    } else {
-                        file_lists_verilog.get(lib_name).unwrap_or(&files_empty)
-                    };
-                    if files.is_empty() {
-                        continue;
-                    }
-                    if *single_call {
-                        call = vec!["    ".to_string()];
-                    } else {
-                        call = common.clone();
-                    }
-                    for value in per_lib {
-                        call.push(value.clone());
-                        if *single_call {
-                            call.push(String::from(" \\"))
-                        } else {
-                            call.push(String::from(" \\\n    "))
-                        }
-                    }
-                    let sep = if *single_call {
-                        " \\\n        "
-                    } else {
-                        " \\\n    "
-                    };
-                    let tmp = call.join("");
-                    let tmp = tmp.replace("{library}", lib_name);
-                    let tmp = tmp.replace("{files}", &files.join(sep));
-                    content.push(tmp);
+                if !single_libs.is_empty() {
+                    single.library = single_libs.join(", ");
+                    let commons = [main_common.clone(), lang_common.clone()];
+                    let mut fragments = expand_fragments(&commons, &single_libs.join(" "), &single.files);
+                    fragments.append(&mut single.fragments);
+                    single.fragments = fragments;
+                    single.cwd = tool_config.cwd.clone();
+                    single.env = tool_config.env.clone();
+                    ret.push(single);
                 }
-                content.push("".to_string());
             }
         }
     }
 
-    //res.insert("libraries".to_string(), lib_order);
-    content.push("".to_string());
     // exec_after
     for entry in &tool_config.exec_after {
-        content.push(entry.clone());
+        ret.push(shell_call("after", "", entry.clone(), tool_config));
+    }
+    // elaborate and run
+    for stage in ["elaborate", "run"] {
+        if stop_after == "compile" || (stop_after == "elaborate" && stage == "run") {
+            break;
+        }
+        for top in toplevels {
+            ret.extend(get_stage_calls(tool_config, top, stage, generics));
+        }
+    }
+    ret
+}
+
+fn compile_call(
+    tool_config: &ToolConfig, lang: &str, main_common: &str, lang_common: &str, lib_name: &str,
+    files: &[String],
+) -> ToolCall {
+    let lang_config = if lang == "vhdl" {
+        &tool_config.vhdl
+    } else {
+        &tool_config.verilog
+    };
+    let mut call = ToolCall::new("compile", lib_name, lang);
+    call.fragments = expand_fragments(&[main_common.to_string(), lang_common.to_string()], lib_name, files);
+    call.fragments.extend(expand_fragments(&lang_config.per_lib, lib_name, files));
+    call.files = files.to_vec();
+    call.cwd = tool_config.cwd.clone();
+    call.env = tool_config.env.clone();
+    call
+}
+
+// compile calls for some files of one library, one per common and language common entry
+pub fn get_compile_calls(tool_config: &ToolConfig, lang: &str, lib_name: &str, files: &[String]) -> Vec<ToolCall> {
+    let lang_config = if lang == "vhdl" {
+        &tool_config.vhdl
+    } else {
+        &tool_config.verilog
+    };
+    let tmp: Vec<String> = vec![String::from("")];
+    let lang_commons = if lang_config.common.is_empty() {
+        &tmp
+    } else {
+        &lang_config.common
+    };
+    let mut ret: Vec<ToolCall> = Vec::new();
+    for main_common in &tool_config.common {
+        for lang_common in lang_commons {
+            ret.push(compile_call(tool_config, lang, main_common, lang_common, lib_name, files));
+        }
+    }
+    ret
+}
+
+// calls of the elaborate or run stage
+pub fn get_stage_calls(tool_config: &ToolConfig, toplevel: &str, stage: &str, generics: &[(String, String)]) -> Vec<ToolCall> {
+    let stage_config = if stage == "elaborate" {
+        &tool_config.elaborate
+    } else {
+        &tool_config.run
+    };
+    let mut ret: Vec<ToolCall> = Vec::new();
+    for entry in &stage_config.commands {
+        let entry = entry.replace("{generics}", &render_generics(&tool_config.generic_format, generics));
+        let mut call = shell_call(stage, "", replace_top(&entry, toplevel), tool_config);
+        if !stage_config.cwd.is_empty() {
+            call.cwd = stage_config.cwd.clone();
+        }
+        call.env.extend(stage_config.env.clone());
+        ret.push(call);
+    }
+    ret
+}
+
+pub fn render_script(calls: &[ToolCall], levels: &[Vec<String>]) -> String {
+    let mut content: Vec<String> = Vec::new();
+    content.push(String::from("#!/usr/bin/env sh"));
+    let mut stage = "before";
+    let mut i = 0;
+    while i < calls.len() {
+        let call = &calls[i];
+        if is_library_compile(call, levels) {
+            let mut j = i;
+            while j < calls.len() && is_library_compile(&calls[j], levels) {
+                j += 1;
+            }
+            content.push(String::from(""));
+            content.extend(render_parallel_compile(&calls[i..j], levels));
+            stage = "compile";
+            i = j;
+            continue;
+        }
+        if call.stage != stage || call.stage == "compile" {
+            content.push(String::from(""));
+            stage = call.stage.as_str();
+        }
+        content.push(call.to_script());
+        i += 1;
+    }
+    content.push(String::from(""));
+    content.join("\n")
+}
+
+fn is_library_compile(call: &ToolCall, levels: &[Vec<String>]) -> bool {
+    call.stage == "compile" && levels.iter().any(|level| level.contains(&call.library))
+}
+
+// compile calls of one level run as background jobs, the next level starts when all succeeded
+fn render_parallel_compile(calls: &[ToolCall], levels: &[Vec<String>]) -> Vec<String> {
+    let mut content: Vec<String> = Vec::new();
+    for level in levels {
+        let libs: Vec<&String> = level
+            .iter()
+            .filter(|lib| calls.iter().any(|c| &c.library == *lib))
+            .collect();
+        if libs.is_empty() {
+            continue;
+        }
+        content.push(String::from("pids=\"\""));
+        for lib in libs {
+            content.push(String::from("("));
+            content.push(String::from("set -e"));
+            for call in calls.iter().filter(|c| &c.library == lib) {
+                content.push(call.to_script());
+            }
+            content.push(String::from(") &"));
+            content.push(String::from("pids=\"$pids $!\""));
+        }
+        content.push(String::from("fail=0"));
+        content.push(String::from("for pid in $pids; do"));
+        content.push(String::from("    wait $pid || fail=1"));
+        content.push(String::from("done"));
+        content.push(String::from("[ $fail -eq 0 ] || exit 1"));
+        content.push(String::from(""));
     }
-    content.push("".to_string());
-    let content = content.join("\n");
+    content
+}
+
+pub fn gen_script(
+    lib_name: String, toplevels: &[String], libraries_toml_filename: &String, tool_toml_filename: &String,
+    replacements: &HashMap<String, String>, filename: &String, options: &ScriptOptions,
+) {
+    let (element_list, libraries) = get_element_list(lib_name.clone(), toplevels, libraries_toml_filename, tool_toml_filename, replacements, &options.overrides);
+    let lib_order = get_sorted_libraries(&libraries);
+    let tool_config = read_tool_toml(tool_toml_filename, &replacements);
+    // elaborate and run every toplevel
+    let toplevels = expand_toplevels(&libraries, &lib_name, toplevels);
+
+    match element_list.last() {
+        None => {
+            eprintln!("No files found to write out!");
+            exit(1);
+        }
+        Some(last_el) => last_el,
+    };
+    let content = match options.format.as_str() {
+        "questa" => render_questa_script(&get_compile_order(&element_list, &lib_order), &options.vhdl_std, &options.lib_dir),
+        "vivado" => render_vivado_script(&get_compile_order(&element_list, &lib_order), &options.vhdl_std),
+        _ => {
+            let calls = get_tool_calls(&element_list, &lib_order, &tool_config, &toplevels, &options.stop_after, &options.generics);
+            let levels = if options.parallel {
+                get_library_levels(&libraries)
+            } else {
+                Vec::new()
+            };
+            render_script(&calls, &levels)
+        }
+    };
     let mut file = File::create(filename).unwrap();
     let _ = file.write(content.as_ref());
-    fs::set_permissions(filename, fs::Permissions::from_mode(0o770)).unwrap();
+    // only the shell script is executed directly, questa and vivado source theirs
+    if !matches!(options.format.as_str(), "questa" | "vivado") {
+        fs::set_permissions(filename, fs::Permissions::from_mode(0o770)).unwrap();
+    }
     println!("{} was written!", filename)
 }
 
-pub fn run_script(filename: &String) {
-    let file_name = if !filename.starts_with('/') {
-        "./".to_owned() + filename
+pub fn execute(
+    lib_name: String, toplevels: &[String], libraries_toml_filename: &String, tool_toml_filename: &String,
+    replacements: &HashMap<String, String>, options: &ScriptOptions,
+) -> i32 {
+    let (element_list, libraries) = get_element_list(lib_name.clone(), toplevels, libraries_toml_filename, tool_toml_filename, replacements, &options.overrides);
+    let lib_order = get_sorted_libraries(&libraries);
+    let tool_config = read_tool_toml(tool_toml_filename, replacements);
+    // elaborate and run every toplevel
+    let toplevels = expand_toplevels(&libraries, &lib_name, toplevels);
+
+    if element_list.is_empty() {
+        eprintln!("No files found to compile!");
+        return 1;
+    }
+    // only changed files and the ones depending on them need to be compiled
+    let stamps_filename = get_stamps_filename(&options.stamps_dir, &toplevels.join(","), tool_toml_filename);
+    let tool_hash = get_tool_hash(tool_toml_filename, replacements, &options.generics, &options.overrides);
+    let (old_tool_hash, mut stamps) = if options.full {
+        (String::new(), HashMap::new())
     } else {
-        filename.clone()
+        read_stamps(&stamps_filename)
     };
-    let mut cmd = Command::new(file_name);
-    let status = cmd.status();
-    let exit_code = match status {
-        Ok(exit_status) => exit_status.code().unwrap_or_else(|| {
-            eprintln!("Error: No exit status from script");
-            21
-        }),
-        Err(e) => {
-            eprintln!("Error: {}", e);
-            eprintln!("       cmd was:   {:?}", cmd.get_program());
-            eprintln!("       args were: {:?}", cmd.get_args());
-            eprintln!("       cwd:       {:?}", env::current_dir().unwrap());
-            1
+    if old_tool_hash != tool_hash {
+        stamps.clear();
+    }
+    let mut changed: Vec<String> = Vec::new();
+    for el in &element_list {
+        if !changed.contains(&el.filename) && stamps.get(&el.filename) != Some(&hash_file(&el.filename)) {
+            changed.push(el.filename.clone());
         }
-    };
-    exit(exit_code);
+    }
+    let affected = get_affected_files(&element_list, &changed);
+    let compile_list: Vec<Element> = element_list
+        .iter()
+        .filter(|el| affected.contains(&el.filename))
+        .map(|el| el.copy())
+        .collect();
+    info!("{} changed file(s), {} file(s) to compile", changed.len(), affected.len());
+
+    let calls = get_tool_calls(&compile_list, &lib_order, &tool_config, &toplevels, &options.stop_after, &options.generics);
+    let results = run_tool_calls(&calls, &get_library_dependencies(&libraries), options.jobs);
+    print_summary(&results);
+    let mut compiled: Vec<&String> = Vec::new();
+    for (call, result) in calls.iter().zip(&results) {
+        if call.stage == "compile" && result.status == "ok" {
+            for filename in &call.files {
+                stamps.insert(filename.clone(), hash_file(filename));
+                compiled.push(filename);
+            }
+        }
+    }
+    // files which failed or weren't compiled are out of date, even if their source didn't change
+    for filename in affected.iter().filter(|f| !compiled.contains(f)) {
+        stamps.remove(filename);
+    }
+    write_stamps(&stamps_filename, &tool_hash, &stamps);
+    match results.iter().find(|r| r.status == "failed") {
+        None => 0,
+        Some(failed) => if failed.exit_code != 0 { failed.exit_code } else { 1 },
+    }
 }
 
-pub fn get_sorted_libraries(libraries: &HashMap<String, Library>) -> Vec<String> {
+// files each file of the list directly depends on
+pub fn get_file_dependencies(element_list: &Vec<Element>) -> HashMap<String, Vec<String>> {
+    let mut ret: HashMap<String, Vec<String>> = HashMap::new();
+    for el in element_list {
+        let deps = ret.entry(el.filename.clone()).or_default();
+        for dep in &el.depends_on {
+            if dep.filename != el.filename && !dep.filename.is_empty() && !deps.contains(&dep.filename) {
+                deps.push(dep.filename.clone());
+            }
+        }
+    }
+    ret
+}
+
+// changed files and all files depending on them, in compile order
+pub fn get_affected_files(element_list: &Vec<Element>, changed: &[String]) -> Vec<String> {
+    let mut dependents: HashMap<String, Vec<String>> = HashMap::new();
+    for (filename, deps) in get_file_dependencies(element_list) {
+        for dep in deps {
+            dependents.entry(dep).or_default().push(filename.clone());
+        }
+    }
+    let mut affected: Vec<String> = changed.to_vec();
+    let mut todo: Vec<String> = changed.to_vec();
+    while let Some(filename) = todo.pop() {
+        for dependent in dependents.get(&filename).unwrap_or(&Vec::new()) {
+            if !affected.contains(dependent) {
+                affected.push(dependent.clone());
+                todo.push(dependent.clone());
+            }
+        }
+    }
     let mut ret: Vec<String> = Vec::new();
+    for el in element_list {
+        if affected.contains(&el.filename) && !ret.contains(&el.filename) {
+            ret.push(el.filename.clone());
+        }
+    }
+    ret
+}
+
+// stamp file of each file of the list, relative to the stamp directory
+pub fn get_stamp_names(element_list: &Vec<Element>) -> HashMap<String, String> {
+    let mut ret: HashMap<String, String> = HashMap::new();
+    for el in element_list {
+        if ret.contains_key(&el.filename) {
+            continue;
+        }
+        let base = Path::new(&el.filename)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("file");
+        let mut name = format!("{}/{}.stamp", el.library, base);
+        let mut count = 1;
+        while ret.values().any(|v| v == &name) {
+            count += 1;
+            name = format!("{}/{}_{}.stamp", el.library, base, count);
+        }
+        ret.insert(el.filename.clone(), name);
+    }
+    ret
+}
+
+pub fn get_sorted_libraries(libraries: &HashMap<String, Library>) -> Vec<String> {
+    get_library_levels(libraries).concat()
+}
+
+// libraries grouped by levels, libraries of one level only depend on the ones of the levels before
+pub fn get_library_levels(libraries: &HashMap<String, Library>) -> Vec<Vec<String>> {
+    let mut ret: Vec<Vec<String>> = Vec::new();
+    let mut done: Vec<String> = Vec::new();
     let mut libs_remaining: Vec<String> = libraries.keys().cloned().collect();
 
     // let's start with the empty ones!
+    let mut libs_first: Vec<String> = Vec::new();
     for (name, lib) in libraries {
-        if !lib.ignore && lib.depends_on_libs.is_empty() {
-            ret.push(name.clone());
+        if lib.is_compiled() && lib.depends_on_libs.is_empty() {
+            libs_first.push(name.clone());
             libs_remaining.retain(|x| x != name);
         }
     }
-    ret.sort();
+    libs_first.sort();
+    done.extend(libs_first.iter().cloned());
+    if !libs_first.is_empty() {
+        ret.push(libs_first);
+    }
     // let's do the rest
     while !libs_remaining.is_empty() {
         let mut libs_new: Vec<String> = Vec::new();
         for name in libs_remaining.clone() {
             let lib = libraries.get(&name).unwrap();
-            if lib.ignore {
+            if !lib.is_compiled() {
                 libs_remaining.retain(|x| x != &name);
                 continue;
             }
@@ -825,12 +1808,12 @@ pub fn get_sorted_libraries(libraries: &HashMap<String, Library>) -> Vec<String>
                 match libraries.get(lib_name_dep) {
                     None => continue,
                     Some(l) => {
-                        if l.ignore {
+                        if !l.is_compiled() {
                             continue;
                         }
                     }
                 }
-                if !ret.contains(lib_name_dep) {
+                if !done.contains(lib_name_dep) {
                     all_available = false;
                 }
             }
@@ -840,11 +1823,33 @@ pub fn get_sorted_libraries(libraries: &HashMap<String, Library>) -> Vec<String>
             }
         }
         if libs_new.is_empty() {
-            println!("ohoh! {:?} / {:?}", ret, libs_remaining);
+            if libs_remaining.is_empty() {
+                break;
+            }
+            println!("ohoh! {:?} / {:?}", done, libs_remaining);
             exit(1);
         }
         libs_new.sort();
-        ret.append(&mut libs_new);
+        done.extend(libs_new.iter().cloned());
+        ret.push(libs_new);
+    }
+    ret
+}
+
+// libraries each library depends on, restricted to the known and compiled ones
+pub fn get_library_dependencies(libraries: &HashMap<String, Library>) -> HashMap<String, Vec<String>> {
+    let mut ret: HashMap<String, Vec<String>> = HashMap::new();
+    for (name, lib) in libraries {
+        if !lib.is_compiled() {
+            continue;
+        }
+        let deps: Vec<String> = lib
+            .depends_on_libs
+            .iter()
+            .filter(|dep| libraries.get(*dep).is_some_and(|l| l.is_compiled()))
+            .cloned()
+            .collect();
+        ret.insert(name.clone(), deps);
     }
     ret
 }
@@ -862,6 +1867,13 @@ verilog = [\"ref_design/lib_design/*.v\"]
 [lib_to_ignore]
 ignore=true
 
+# precompiled library, not compiled but mapped by 'hanna libmap'
+[unisim]
+precompiled = \"/tools/xilinx/unisim\"
+
+# vendor libraries known to the simulator
+external_libraries = [\"xpm\", \"altera_mf\"]
+
 
 ===================================================================
 tool.toml
@@ -877,6 +1889,7 @@ exec_before = [\"Mach was vorher\"]
 exec_after = [\"Mach was danach\"]
 exec_per_lib = [\"echo mache {{library}}\"]
 replace = {{ \"heiko\" = \"braun\" }}
+generic_format = \"-g{{name}}={{value}}\"  # rendering of each generic for {{generics}} in elaborate and run
 
 [vhdl]
 common = [\"-v93\"]
@@ -889,7 +1902,23 @@ replace = {{ \"heiko2\" = \"braun2\" }}
 common = [\"verilog_com1\"]
 per_lib = [\"-work_verilog {{library}}\", \"{{files}}\"]
 single_call = false
-exec_per_lib = [\"echo {{library}} verilog\"]"
+exec_per_lib = [\"echo {{library}} verilog\"]
+
+# toplevel placeholders: {{toplevel}}, {{top_library}}, {{top}}, {{top_arch}}
+[elaborate]
+commands = [\"echo elaborate {{top_library}}.{{top}}\"]
+
+[run]
+commands = [\"echo run {{top_library}}.{{top}}\"]
+
+# hanna test: elaborate and run of each testbench in run_dir
+[test]
+fail_patterns = [\"FAILURE\", \"Error:\"]
+pass_patterns = []
+timeout = 600
+run_dir = \".hanna/test/{{toplevel}}\"
+# one run per table, -g overrides single values
+generics = {{ \"lib_1.cfg_tb_*\" = [{{ WIDTH = 8 }}, {{ WIDTH = 16 }}] }}"
     )
 }
 
@@ -897,8 +1926,26 @@ exec_per_lib = [\"echo {{library}} verilog\"]"
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
 
-    use crate::write_json_file;
+    use crate::{execute, expand_toplevels, pre_work_file_content, gen_script, get_affected_files, get_architecture_conflicts, get_compile_order, get_element_list, get_toplevel_elements, get_hierarchy, get_libraries, get_library_levels, get_sorted_libraries, get_tool_calls, read_tool_toml, write_json_file};
+    use crate::affected::get_affected_toplevels;
+    use crate::check::{check_components, check_configurations};
+    use crate::classes::{BindingOverrides, Element, Library, ScriptOptions, TestOptions, ToolCall, TreeOptions, RE_CONF_COMP_SPEC};
+    use crate::execute::{run_tool_calls, summary_lines};
+    use crate::gen_config::get_configuration;
+    use crate::graph::{get_hierarchy_graph, get_units_graph, render_dot, render_mermaid};
+    use crate::libmap::write_libmap;
+    use crate::makefile::write_makefile;
+    use crate::ninja::write_ninja_file;
+    use crate::roots::get_roots;
+    use crate::stamps::{get_stamps_filename, get_tool_hash, read_stamps};
+    use crate::test_runner::{run_test, run_tests};
+    use crate::tree::{filter_tree, get_instance_tree, tree_lines};
+    use crate::unused::{get_unreferenced_units, get_unused_files};
+    use crate::why::{chain_lines, find_chain};
+    use crate::vhdl_ls::write_vhdl_ls_toml;
 
     #[test]
     fn test_filelist_design_1() {
@@ -906,7 +1953,7 @@ mod tests {
         let libraries_toml_path = String::from("tomls/libraries.toml");
         let tool_toml_path = String::from("tomls/tools/echo.toml");
         let replacements: HashMap<String, String> = HashMap::new();
-        write_json_file(String::from("lib_1"), String::from("lib_1.design_1(rtl)"), &libraries_toml_path, &tool_toml_path, &replacements, &json_filename);
+        write_json_file(String::from("lib_1"), &[String::from("lib_1.design_1(rtl)")], &libraries_toml_path, &tool_toml_path, &replacements, &BindingOverrides::new(), &json_filename);
         assert_eq!(2, 2);
     }
 
@@ -916,8 +1963,799 @@ mod tests {
         let libraries_toml_path = String::from("tomls/libraries.toml");
         let tool_toml_path = String::from("tomls/tools/echo.toml");
         let replacements: HashMap<String, String> = HashMap::new();
-        write_json_file(String::from("lib_1"), String::from("lib_1.cfg_testbench_1"), &libraries_toml_path, &tool_toml_path, &replacements, &json_filename);
-        assert_eq!(2, 2);
+        write_json_file(String::from("lib_1"), &[String::from("lib_1.cfg_testbench_1")], &libraries_toml_path, &tool_toml_path, &replacements, &BindingOverrides::new(), &json_filename);
+        let content: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&json_filename).unwrap()).unwrap();
+        assert_eq!(content["version"], 1);
+        assert_eq!(content["libraries"][0]["name"], "lib_1");
+        let files = content["files"].as_array().unwrap();
+        assert!(files.last().unwrap()["filename"].as_str().unwrap().ends_with("cfg_testbench_1.vhd"));
+        assert_eq!(files.last().unwrap()["order"], files.len() - 1);
+        assert_eq!(content["toplevels"], serde_json::json!(["lib_1.cfg_testbench_1"]));
+        assert_eq!(content["hierarchies"][0]["unit"], "cfg_testbench_1");
+        assert!(!content["hierarchies"][0]["children"].as_array().unwrap().is_empty());
+
+        // one hierarchy per toplevel
+        write_json_file(String::from("lib_1"), &[String::from("lib_1.cfg_*")], &libraries_toml_path, &tool_toml_path, &replacements, &BindingOverrides::new(), &json_filename);
+        let content: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&json_filename).unwrap()).unwrap();
+        assert_eq!(content["toplevels"], serde_json::json!(["lib_1.cfg_design_1", "lib_1.cfg_testbench_1"]));
+        let units: Vec<&str> = content["hierarchies"].as_array().unwrap().iter().map(|h| h["unit"].as_str().unwrap()).collect();
+        assert_eq!(units, vec!["cfg_design_1", "cfg_testbench_1"]);
+    }
+
+    #[test]
+    fn test_script_elaborate_run() {
+        let script_filename = std::env::temp_dir().join("hanna_test_elaborate_run.sh");
+        let script_filename = String::from(script_filename.to_str().unwrap());
+        let libraries_toml_path = String::from("tomls/libraries.toml");
+        let tool_toml_path = String::from("tomls/tools/ghdl.toml");
+        let replacements: HashMap<String, String> = HashMap::new();
+        gen_script(String::from("lib_1"), &[String::from("lib_1.cfg_testbench_1")], &libraries_toml_path, &tool_toml_path, &replacements, &script_filename, &ScriptOptions { stop_after: String::from("elaborate"), ..ScriptOptions::new() });
+        let content = std::fs::read_to_string(&script_filename).unwrap();
+        assert!(content.contains("ghdl elaborate --std=08 --work=lib_1 cfg_testbench_1"));
+        assert!(!content.contains("ghdl run"));
+    }
+
+    #[test]
+    fn test_script_formats() {
+        let libraries_toml_path = String::from("tomls/libraries.toml");
+        let tool_toml_path = String::from("tomls/tools/ghdl.toml");
+        let replacements: HashMap<String, String> = HashMap::new();
+        let questa_filename = std::env::temp_dir().join("hanna_test_compile.do");
+        let questa_filename = String::from(questa_filename.to_str().unwrap());
+        let _ = fs::remove_file(&questa_filename);
+        gen_script(String::from("lib_1"), &[String::from("lib_1.cfg_testbench_1")], &libraries_toml_path, &tool_toml_path, &replacements, &questa_filename, &ScriptOptions { format: String::from("questa"), ..ScriptOptions::new() });
+        let content = std::fs::read_to_string(&questa_filename).unwrap();
+        assert_eq!(fs::metadata(&questa_filename).unwrap().permissions().mode() & 0o111, 0);
+        assert!(content.contains("vlib work/lib_1"));
+        assert!(content.contains("vmap lib_1 work/lib_1"));
+        assert!(content.contains("vcom -2008 -work lib_1"));
+        let vivado_filename = std::env::temp_dir().join("hanna_test_compile.tcl");
+        let vivado_filename = String::from(vivado_filename.to_str().unwrap());
+        let _ = fs::remove_file(&vivado_filename);
+        gen_script(String::from("lib_1"), &[String::from("lib_1.cfg_testbench_1")], &libraries_toml_path, &tool_toml_path, &replacements, &vivado_filename, &ScriptOptions { format: String::from("vivado"), ..ScriptOptions::new() });
+        let content = std::fs::read_to_string(&vivado_filename).unwrap();
+        assert_eq!(fs::metadata(&vivado_filename).unwrap().permissions().mode() & 0o111, 0);
+        assert!(content.contains("read_vhdl -library lib_1 -vhdl2008"));
+        assert!(content.find("design_1.vhd").unwrap() < content.find("cfg_testbench_1.vhd").unwrap());
+    }
+
+    #[test]
+    fn test_external_libraries() {
+        let libraries_toml_path = String::from("tomls/libraries_external.toml");
+        let tool_toml_path = String::from("tomls/tools/ghdl.toml");
+        let replacements: HashMap<String, String> = HashMap::new();
+        let (element_list, libraries) = get_element_list(String::from("lib_ext"), &[String::from("lib_ext.top_ext")], &libraries_toml_path, &tool_toml_path, &replacements, &BindingOverrides::new());
+        assert!(element_list.iter().all(|el| el.library == "lib_ext"));
+        let top = element_list.last().unwrap();
+        assert!(top.depends_on.iter().any(|dep| dep.library == "unisim" && dep.kind == "instance"));
+        assert!(top.depends_on.iter().any(|dep| dep.library == "xpm" && dep.kind == "instance"));
+        assert_eq!(get_sorted_libraries(&libraries), vec![String::from("lib_ext")]);
+    }
+
+    #[test]
+    fn test_vhdl_ls_toml() {
+        let vhdl_ls_filename = std::env::temp_dir().join("hanna_test_vhdl_ls.toml");
+        let vhdl_ls_filename = String::from(vhdl_ls_filename.to_str().unwrap());
+        let libraries_toml_path = String::from("tomls/libraries.toml");
+        let tool_toml_path = String::from("tomls/tools/echo.toml");
+        let replacements: HashMap<String, String> = HashMap::new();
+        write_vhdl_ls_toml(String::from("lib_1"), &[String::from("lib_1.design_1(rtl)")], &libraries_toml_path, &tool_toml_path, &replacements, &BindingOverrides::new(), &vhdl_ls_filename);
+        let content: toml::Table = std::fs::read_to_string(&vhdl_ls_filename).unwrap().parse().unwrap();
+        let files = content["libraries"]["lib_1"]["files"].as_array().unwrap();
+        assert!(files.iter().any(|f| f.as_str().unwrap().ends_with("design_1_rtl.vhd")));
+        assert!(!files.iter().any(|f| f.as_str().unwrap().ends_with("testbench.vhd")));
+    }
+
+    #[test]
+    fn test_instance_tree() {
+        let libraries_toml_path = String::from("tomls/libraries_external.toml");
+        let tool_toml_path = String::from("tomls/tools/ghdl.toml");
+        let replacements: HashMap<String, String> = HashMap::new();
+        let (element_list, _) = get_element_list(String::from("lib_ext"), &[String::from("lib_ext.top_ext")], &libraries_toml_path, &tool_toml_path, &replacements, &BindingOverrides::new());
+        let tree = get_instance_tree(&get_hierarchy(&element_list));
+        let lines = tree_lines(&tree);
+        assert_eq!(lines[0], "lib_ext.top_ext(rtl) [vhdl]");
+        assert!(lines.contains(&String::from("├── i_fdre: unisim.fdre [external] (external)")));
+        assert!(lines.contains(&String::from("└── i_cdc: xpm.xpm_cdc_single [external] (entity)")));
+        let options = TreeOptions { libraries: vec![String::from("xpm")], ..TreeOptions::new() };
+        let filtered = filter_tree(&tree, &options, 0).unwrap();
+        assert_eq!(filtered.children.len(), 1);
+        assert_eq!(filtered.children[0].label, "i_cdc");
+    }
+
+    #[test]
+    fn test_graph() {
+        let libraries_toml_path = String::from("tomls/libraries.toml");
+        let tool_toml_path = String::from("tomls/tools/echo.toml");
+        let replacements: HashMap<String, String> = HashMap::new();
+        let (element_list, _) = get_element_list(String::from("lib_1"), &[String::from("lib_1.cfg_testbench_1")], &libraries_toml_path, &tool_toml_path, &replacements, &BindingOverrides::new());
+        let graph = get_units_graph(&get_hierarchy(&element_list));
+        let dot = render_dot(&graph);
+        assert!(dot.starts_with("digraph \"lib_1.cfg_testbench_1\" {"));
+        assert!(dot.contains("[label=\"lib_1.design_1(rtl)\"]"));
+        assert!(!dot.contains("style=dashed"));
+        let mermaid = render_mermaid(&get_hierarchy_graph(&get_instance_tree(&get_hierarchy(&element_list))));
+        assert!(mermaid.contains("n0 -->|\"duv_i\"| n1"));
+    }
+
+    #[test]
+    fn test_why() {
+        let libraries_toml_path = String::from("tomls/libraries.toml");
+        let tool_toml_path = String::from("tomls/tools/echo.toml");
+        let replacements: HashMap<String, String> = HashMap::new();
+        let (element_list, _) = get_element_list(String::from("lib_1"), &[String::from("lib_1.cfg_testbench_1")], &libraries_toml_path, &tool_toml_path, &replacements, &BindingOverrides::new());
+        let chain = find_chain(&element_list, "design_1_rtl.vhd").unwrap();
+        let units: Vec<&String> = chain.iter().map(|d| &d.unit).collect();
+        assert_eq!(units, vec!["testbench(structure)", "cfg_design_1", "design_1(rtl)"]);
+        let lines = chain_lines(element_list.last().unwrap(), &chain);
+        assert!(lines[2].starts_with("-> duv_i: lib_1.cfg_design_1"));
+        assert!(lines[2].ends_with("testbench.vhd:53)"));
+        assert!(find_chain(&element_list, "package_1.vhd").is_none());
+    }
+
+    #[test]
+    fn test_affected_toplevels() {
+        let libraries_toml_path = String::from("tomls/libraries.toml");
+        let tool_toml_path = String::from("tomls/tools/echo.toml");
+        let replacements: HashMap<String, String> = HashMap::new();
+        let libraries = get_libraries(&libraries_toml_path, &tool_toml_path, &replacements);
+        let affected = get_affected_toplevels(&libraries, &[String::from("rtl/lib_1/design_1.vhd")]);
+        let toplevels: Vec<&String> = affected.iter().map(|(t, _)| t).collect();
+        assert!(toplevels.contains(&&String::from("lib_1.cfg_testbench_1")));
+        // design_1 has ports, so it's no candidate itself
+        assert!(!toplevels.contains(&&String::from("lib_1.design_1(rtl)")));
+        let affected = get_affected_toplevels(&libraries, &[String::from("rtl/lib_1/package_1.vhd")]);
+        assert!(affected.is_empty());
+    }
+
+    #[test]
+    fn test_execute_echo() {
+        let libraries_toml_path = String::from("tomls/libraries.toml");
+        let tool_toml_path = String::from("tomls/tools/echo_stages.toml");
+        let replacements: HashMap<String, String> = HashMap::new();
+        let stamps_dir = std::env::temp_dir().join("hanna_test_stamps");
+        let options = ScriptOptions {
+            full: true,
+            stamps_dir: String::from(stamps_dir.to_str().unwrap()),
+            ..ScriptOptions::new()
+        };
+        let exit_code = execute(String::from("lib_1"), &[String::from("lib_1.cfg_testbench_1")], &libraries_toml_path, &tool_toml_path, &replacements, &options);
+        assert_eq!(exit_code, 0);
+    }
+
+    #[test]
+    fn test_tool_calls() {
+        let call = |stage: &str, library: &str, fragments: &[&str]| {
+            let mut call = ToolCall::new(stage, library, "vhdl");
+            call.fragments = fragments.iter().map(|f| f.to_string()).collect();
+            call
+        };
+        let mut failing = call("compile", "lib_b", &["sh -c 'echo error in b.vhd; exit 3'"]);
+        failing.files = vec![String::from("b.vhd")];
+        let calls = vec![call("compile", "lib_a", &["echo", "'two words'"]), failing, call("elaborate", "", &["echo never"])];
+        let results = run_tool_calls(&calls, &HashMap::new(), 1);
+        // quoted arguments are kept together
+        assert_eq!(results[0].status, "ok");
+        assert_eq!(results[0].output, vec!["two words"]);
+        assert_eq!((results[1].status.as_str(), results[1].exit_code, results[1].failed_file.as_str()), ("failed", 3, "b.vhd"));
+        // nothing runs after the first failure
+        assert_eq!(results[2].status, "skipped");
+        assert!(results[2].output.is_empty());
+        let lines = summary_lines(&results);
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("Stage      Library Status"));
+        assert!(lines[2].starts_with("compile    lib_b   failed") && lines[2].ends_with("exit code 3, b.vhd"));
+        assert_eq!(lines[3], "elaborate          skipped");
+
+        let mut quoted = call("compile", "lib_a", &["vcom", "a.vhd"]);
+        quoted.cwd = String::from("/tmp/my dir");
+        quoted.env.insert(String::from("OPTS"), String::from("-a -b"));
+        assert_eq!(quoted.to_line(), "(cd '/tmp/my dir' && OPTS='-a -b' vcom a.vhd)");
+    }
+
+    #[test]
+    fn test_common_fragments() {
+        let dir = std::env::temp_dir().join("hanna_test_common");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let filename = String::from(dir.join("a.vhd").to_str().unwrap());
+        fs::write(&filename, "entity a is\nend entity;\narchitecture rtl of a is\nbegin\nend architecture;\n").unwrap();
+        let libraries_toml_path = String::from(dir.join("libraries.toml").to_str().unwrap());
+        fs::write(&libraries_toml_path, format!("[lib_a]\nvhdl = [\"{}\"]\n", filename)).unwrap();
+        // a tool.toml without per_lib, the placeholders are only given in the common values
+        let tool_toml_path = String::from("tomls/tools/common_only.toml");
+        let replacements: HashMap<String, String> = HashMap::new();
+        let toplevels = [String::from("lib_a.a")];
+        let (element_list, libraries) = get_element_list(String::from("lib_a"), &toplevels, &libraries_toml_path, &tool_toml_path, &replacements, &BindingOverrides::new());
+        let tool_config = read_tool_toml(&tool_toml_path, &replacements);
+        let calls = get_tool_calls(&element_list, &get_sorted_libraries(&libraries), &tool_config, &toplevels, &String::from("compile"), &[]);
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].fragments, vec![String::from("echo --work=lib_a"), filename.clone()]);
+        assert_eq!(calls[0].to_args(), vec![String::from("echo"), String::from("--work=lib_a"), filename.clone()]);
+
+        let script_filename = String::from(dir.join("compile.sh").to_str().unwrap());
+        let options = ScriptOptions { stop_after: String::from("compile"), ..ScriptOptions::new() };
+        gen_script(String::from("lib_a"), &toplevels, &libraries_toml_path, &tool_toml_path, &replacements, &script_filename, &options);
+        let content = fs::read_to_string(&script_filename).unwrap();
+        assert!(content.contains(&format!("echo --work=lib_a \\\n    {}", filename)));
+        assert!(!content.contains("{files}") && !content.contains("{library}"));
+    }
+
+    #[test]
+    fn test_parallel_script() {
+        let dir = std::env::temp_dir().join("hanna_test_parallel");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        // lib_c needs lib_a and lib_b, lib_d needs lib_c
+        let sources = [
+            ("lib_a", "entity a is\nend entity;\narchitecture rtl of a is\nbegin\nend architecture;\n"),
+            ("lib_b", "entity b is\nend entity;\narchitecture rtl of b is\nbegin\nend architecture;\n"),
+            ("lib_c", "library lib_a;\nlibrary lib_b;\nentity c is\nend entity;\narchitecture rtl of c is\nbegin\n  a_i: entity lib_a.a;\n  b_i: entity lib_b.b;\nend architecture;\n"),
+            ("lib_d", "library lib_c;\nentity d is\nend entity;\narchitecture rtl of d is\nbegin\n  c_i: entity lib_c.c;\nend architecture;\n"),
+        ];
+        let mut toml = String::new();
+        for (lib, source) in sources {
+            let filename = dir.join(format!("{}.vhd", lib));
+            fs::write(&filename, source).unwrap();
+            toml.push_str(&format!("[{}]\nvhdl = [\"{}\"]\n\n", lib, filename.display()));
+        }
+        let libraries_toml_path = String::from(dir.join("libraries.toml").to_str().unwrap());
+        fs::write(&libraries_toml_path, toml).unwrap();
+        let tool_toml_path = String::from("tomls/tools/echo_stages.toml");
+        let replacements: HashMap<String, String> = HashMap::new();
+        let libraries = get_libraries(&libraries_toml_path, &tool_toml_path, &replacements);
+        assert_eq!(get_library_levels(&libraries), vec![vec!["lib_a", "lib_b"], vec!["lib_c"], vec!["lib_d"]]);
+
+        let script_filename = String::from(dir.join("parallel.sh").to_str().unwrap());
+        let options = ScriptOptions { parallel: true, stop_after: String::from("compile"), ..ScriptOptions::new() };
+        gen_script(String::from("lib_d"), &[String::from("lib_d.d")], &libraries_toml_path, &tool_toml_path, &replacements, &script_filename, &options);
+        let content = fs::read_to_string(&script_filename).unwrap();
+        let blocks: Vec<&str> = content.split("pids=\"\"\n").skip(1).collect();
+        assert_eq!(blocks.len(), 3);
+        // each library of a level is compiled in the background, the level waits for all of them
+        let wait = "fail=0\nfor pid in $pids; do\n    wait $pid || fail=1\ndone\n[ $fail -eq 0 ] || exit 1\n";
+        assert!(blocks.iter().all(|b| b.matches("(\nset -e\n").count() == b.matches(") &\npids=\"$pids $!\"\n").count() && b.contains(wait)));
+        assert!(blocks[0].contains("--work=lib_a") && blocks[0].contains("--work=lib_b"));
+        assert_eq!(blocks[0].matches(") &\n").count(), 2);
+        assert!(blocks[1].contains("--work=lib_c") && !blocks[1].contains("--work=lib_a"));
+        assert!(blocks[2].contains("--work=lib_d"));
+        assert!(std::process::Command::new("sh").arg(&script_filename).output().unwrap().status.success());
+    }
+
+    #[test]
+    fn test_execute_incremental_failure() {
+        let dir = std::env::temp_dir().join("hanna_test_incremental");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let file_a = dir.join("a.vhd");
+        let file_b = dir.join("b.vhd");
+        fs::write(&file_a, "entity a is\nend entity;\narchitecture rtl of a is\nbegin\nend architecture;\n").unwrap();
+        fs::write(
+            &file_b,
+            "library lib_a;\nentity b is\nend entity;\narchitecture rtl of b is\nbegin\n  a_i: entity lib_a.a;\nend architecture;\n",
+        )
+        .unwrap();
+        let libraries_toml_path = String::from(dir.join("libraries.toml").to_str().unwrap());
+        fs::write(&libraries_toml_path, format!("[lib_a]\nvhdl = [\"{}\"]\n\n[lib_b]\nvhdl = [\"{}\"]\n", file_a.display(), file_b.display())).unwrap();
+        let tool_toml_path = String::from("tomls/tools/fail_lib.toml");
+        let stamps_dir = String::from(dir.join("stamps").to_str().unwrap());
+        let stamps_filename = get_stamps_filename(&stamps_dir, "lib_b.b", &tool_toml_path);
+        let options = ScriptOptions { stop_after: String::from("compile"), stamps_dir, ..ScriptOptions::new() };
+        let run = |fail: &str| {
+            let replacements = HashMap::from([(String::from("fail"), String::from(fail))]);
+            execute(String::from("lib_b"), &[String::from("lib_b.b")], &libraries_toml_path, &tool_toml_path, &replacements, &options)
+        };
+        let filename_a = String::from(file_a.to_str().unwrap());
+        let filename_b = String::from(file_b.to_str().unwrap());
+
+        assert_eq!(run("none"), 0);
+        let (_, stamps) = read_stamps(&stamps_filename);
+        assert!(stamps.contains_key(&filename_a) && stamps.contains_key(&filename_b));
+        // a changes, a compiles but b fails, so b has to be compiled again next time
+        fs::write(&file_a, "entity a is\nend entity;\narchitecture rtl of a is\nbegin\nend architecture;\n-- changed\n").unwrap();
+        assert_ne!(run("lib_b"), 0);
+        let (_, stamps) = read_stamps(&stamps_filename);
+        assert!(stamps.contains_key(&filename_a));
+        assert!(!stamps.contains_key(&filename_b));
+        assert_eq!(run("none"), 0);
+        let (tool_hash, stamps) = read_stamps(&stamps_filename);
+        assert!(stamps.contains_key(&filename_b));
+        // other replacements, generics or binding overrides invalidate the stamps
+        let replacements = HashMap::from([(String::from("fail"), String::from("none"))]);
+        assert_eq!(tool_hash, get_tool_hash(&tool_toml_path, &replacements, &[], &BindingOverrides::new()));
+        let other_replacements = HashMap::from([(String::from("fail"), String::from("lib_b"))]);
+        assert_ne!(tool_hash, get_tool_hash(&tool_toml_path, &other_replacements, &[], &BindingOverrides::new()));
+        assert_ne!(tool_hash, get_tool_hash(&tool_toml_path, &replacements, &[(String::from("width"), String::from("8"))], &BindingOverrides::new()));
+        let overrides = BindingOverrides { archs: vec![(String::from("a"), String::from("rtl"))], ..BindingOverrides::new() };
+        assert_ne!(tool_hash, get_tool_hash(&tool_toml_path, &replacements, &[], &overrides));
+    }
+
+    #[test]
+    fn test_makefile() {
+        let tool_toml_path = String::from("tomls/tools/echo_stages.toml");
+        let replacements: HashMap<String, String> = HashMap::new();
+        let root = std::env::var("HANNA_ROOT").unwrap();
+        let filename = String::from(std::env::temp_dir().join("hanna_test.mk").to_str().unwrap());
+        write_makefile(String::from("lib_1"), &[String::from("lib_1.cfg_testbench_1")], &String::from("tomls/libraries.toml"), &tool_toml_path, &replacements, &filename, &ScriptOptions { stamps_dir: String::from(".hanna/make"), ..ScriptOptions::new() });
+        let content = fs::read_to_string(&filename).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        let rule = |name: &str| lines.iter().position(|l| l.starts_with(&format!("$(HANNA_STAMPS)/lib_1/{}.stamp:", name))).unwrap();
+        // the first file only waits for its library, each other one for the file before
+        assert_eq!(lines[rule("design_1.vhd")], format!("$(HANNA_STAMPS)/lib_1/design_1.vhd.stamp: {}/rtl/lib_1/design_1.vhd | $(HANNA_STAMPS)/lib_1/.library", root));
+        assert_eq!(lines[rule("design_1.vhd") + 1], format!("\techo analyze --work=lib_1 {}/rtl/lib_1/design_1.vhd", root));
+        assert_eq!(lines[rule("design_1.vhd") + 2], "\t@touch $@");
+        assert_eq!(
+            lines[rule("cfg_design_1.vhd")],
+            format!("$(HANNA_STAMPS)/lib_1/cfg_design_1.vhd.stamp: {}/rtl/lib_1/cfg_design_1.vhd $(HANNA_STAMPS)/lib_1/design_1_rtl.vhd.stamp | $(HANNA_STAMPS)/lib_1/.library $(HANNA_STAMPS)/lib_1/design_1_rtl.vhd.stamp", root)
+        );
+        assert!(rule("design_1.vhd") < rule("design_1_rtl.vhd") && rule("cfg_design_1.vhd") < rule("testbench.vhd") && rule("testbench.vhd") < rule("cfg_testbench_1.vhd"));
+        let elaborate = lines.iter().position(|l| *l == "elaborate: compile").unwrap();
+        assert_eq!(lines[elaborate + 1], "\techo elaborate lib_1.cfg_testbench_1");
+        assert!(lines.contains(&"$(HANNA_STAMPS)/lib_1/.library: | $(HANNA_STAMPS)/.before"));
+
+        // a file depends on the stamp of the file it needs in another library
+        write_makefile(String::from("lib_check"), &[String::from("lib_check.chk_top(sim)")], &String::from("tomls/libraries_check.toml"), &tool_toml_path, &replacements, &filename, &ScriptOptions { stamps_dir: String::from(".hanna/make"), ..ScriptOptions::new() });
+        let content = fs::read_to_string(&filename).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert!(lines.contains(&format!("$(HANNA_STAMPS)/lib_check/chk_top.vhd.stamp: {}/rtl/lib_check/chk_top.vhd $(HANNA_STAMPS)/lib_cfg/mem.vhd.stamp | $(HANNA_STAMPS)/lib_check/.library", root).as_str()));
+        let library = lines.iter().position(|l| *l == "$(HANNA_STAMPS)/lib_cfg/.library: | $(HANNA_STAMPS)/.before").unwrap();
+        assert_eq!(lines[library + 2], "\techo library lib_cfg");
+    }
+
+    #[test]
+    fn test_libmap() {
+        let dir = std::env::temp_dir().join("hanna_test_libmap");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let options = ScriptOptions { format: String::from("all"), ..ScriptOptions::new() };
+        write_libmap(String::from("lib_1"), &[String::from("lib_1.cfg_testbench_1")], &String::from("tomls/libraries.toml"), &String::from("tomls/tools/echo.toml"), &HashMap::new(), dir.to_str().unwrap(), &options);
+        let read = |name: &str| fs::read_to_string(dir.join(name)).unwrap();
+        // lib_2 isn't needed by the toplevel
+        assert_eq!(read("modelsim.ini"), "[Library]\nothers = $MODEL_TECH/../modelsim.ini\nlib_1 = work/lib_1\n");
+        assert_eq!(read("cds.lib"), "SOFTINCLUDE $CDS_INST_DIR/tools/inca/files/cds.lib\nDEFINE lib_1 work/lib_1\n");
+        assert_eq!(read("hdl.var"), "DEFINE WORK lib_1\n");
+        assert_eq!(read("synopsys_sim.setup"), "OTHERS = $VCS_HOME/bin/synopsys_sim.setup\nWORK > lib_1\nlib_1 : work/lib_1\n");
+        assert!(dir.join("work/lib_1").is_dir());
+        assert!(!dir.join("work/lib_2").exists());
+        // unisim is used by top_ext, secureip isn't
+        let options = ScriptOptions { format: String::from("modelsim"), ..ScriptOptions::new() };
+        write_libmap(String::from("lib_ext"), &[String::from("lib_ext.top_ext")], &String::from("tomls/libraries_external.toml"), &String::from("tomls/tools/echo.toml"), &HashMap::new(), dir.to_str().unwrap(), &options);
+        assert_eq!(read("modelsim.ini"), "[Library]\nothers = $MODEL_TECH/../modelsim.ini\nunisim = /tools/xilinx/unisim\nlib_ext = work/lib_ext\n");
+    }
+
+    #[test]
+    fn test_ninja() {
+        let libraries_toml_path = String::from("tomls/libraries.toml");
+        let tool_toml_path = String::from("tomls/tools/echo.toml");
+        let replacements: HashMap<String, String> = HashMap::new();
+        let filename = String::from(std::env::temp_dir().join("hanna_test.ninja").to_str().unwrap());
+        write_ninja_file(String::from("lib_1"), &[String::from("lib_1.cfg_testbench_1")], &libraries_toml_path, &tool_toml_path, &replacements, &filename, &ScriptOptions { stamps_dir: String::from(".hanna/ninja"), ..ScriptOptions::new() });
+        let content = fs::read_to_string(&filename).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        let rule = lines.iter().position(|l| *l == "rule compile_vhdl").unwrap();
+        // the stamp of a file changes with the stamps it depends on, so restat doesn't prune its dependents
+        assert!(lines[rule + 1].contains("cat $in $deps | cksum"));
+        assert!(lines[rule..].iter().take_while(|l| !l.is_empty()).any(|l| *l == "  restat = 1"));
+        let build = |name: &str| lines.iter().position(|l| l.starts_with(&format!("build $stamps/lib_1/{}.stamp:", name))).unwrap();
+        assert_eq!(lines[build("design_1.vhd")], format!("build $stamps/lib_1/design_1.vhd.stamp: compile_vhdl {}/rtl/lib_1/design_1.vhd || $stamps/lib_1/.library", std::env::var("HANNA_ROOT").unwrap()));
+        let testbench = build("cfg_testbench_1.vhd");
+        assert!(lines[testbench].contains(" | $stamps/lib_1/testbench.vhd.stamp || $stamps/lib_1/.library"));
+        assert_eq!(lines[testbench + 1], "  library = lib_1");
+        assert_eq!(lines[testbench + 2], "  deps = $stamps/lib_1/testbench.vhd.stamp");
+        assert!(build("design_1.vhd") < build("design_1_rtl.vhd") && build("cfg_design_1.vhd") < build("testbench.vhd"));
+        assert!(lines.contains(&"build compile: hanna_after $stamps/lib_1/design_1.vhd.stamp $stamps/lib_1/design_1_rtl.vhd.stamp $stamps/lib_1/cfg_design_1.vhd.stamp $stamps/lib_1/testbench.vhd.stamp $stamps/lib_1/cfg_testbench_1.vhd.stamp"));
+    }
+
+    #[test]
+    fn test_affected_files() {
+        let libraries_toml_path = String::from("tomls/libraries.toml");
+        let tool_toml_path = String::from("tomls/tools/echo.toml");
+        let replacements: HashMap<String, String> = HashMap::new();
+        let (element_list, _) = get_element_list(String::from("lib_1"), &[String::from("lib_1.cfg_testbench_1")], &libraries_toml_path, &tool_toml_path, &replacements, &BindingOverrides::new());
+        let find = |name: &str| element_list.iter().find(|el| el.filename.ends_with(name)).unwrap().filename.clone();
+        let affected = get_affected_files(&element_list, &[find("cfg_testbench_1.vhd")]);
+        assert_eq!(affected, vec![find("cfg_testbench_1.vhd")]);
+        let affected = get_affected_files(&element_list, &[find("design_1.vhd")]);
+        assert!(affected.contains(&find("design_1_rtl.vhd")));
+        assert!(affected.contains(&find("cfg_design_1.vhd")));
+        assert!(affected.contains(&find("cfg_testbench_1.vhd")));
+    }
+
+    #[test]
+    fn test_multiple_toplevels() {
+        let libraries_toml_path = String::from("tomls/libraries.toml");
+        let tool_toml_path = String::from("tomls/tools/echo.toml");
+        let replacements: HashMap<String, String> = HashMap::new();
+        let libraries = get_libraries(&libraries_toml_path, &tool_toml_path, &replacements);
+        let toplevels = expand_toplevels(&libraries, "lib_1", &[String::from("lib_1.cfg_*"), String::from("lib_1.cfg_design_1")]);
+        assert_eq!(toplevels, vec!["lib_1.cfg_design_1", "lib_1.cfg_testbench_1"]);
+        let (element_list, libraries) = get_element_list(String::from("lib_1"), &[String::from("lib_1.cfg_*")], &libraries_toml_path, &tool_toml_path, &replacements, &BindingOverrides::new());
+        let order = get_compile_order(&element_list, &get_sorted_libraries(&libraries));
+        let lib_1 = &order.iter().find(|l| l.library == "lib_1").unwrap().files;
+        let position = |name: &str| lib_1.iter().position(|f| f.ends_with(name)).unwrap();
+        assert_eq!(lib_1.iter().filter(|f| f.ends_with("/cfg_design_1.vhd")).count(), 1);
+        assert!(position("design_1_rtl.vhd") < position("cfg_design_1.vhd"));
+        assert!(position("cfg_design_1.vhd") < position("cfg_testbench_1.vhd"));
+        // each toplevel is elaborated on its own
+        let tool_config = read_tool_toml(&String::from("tomls/tools/echo_stages.toml"), &replacements);
+        let calls = get_tool_calls(&element_list, &get_sorted_libraries(&libraries), &tool_config, &toplevels, &String::from("elaborate"), &[]);
+        let elaborate: Vec<String> = calls.iter().filter(|c| c.stage == "elaborate").map(|c| c.fragments.join(" ")).collect();
+        assert_eq!(elaborate, vec!["echo elaborate lib_1.cfg_design_1", "echo elaborate lib_1.cfg_testbench_1"]);
+        // a pattern matching a single toplevel
+        let (element_list, _) = get_element_list(String::from("lib_1"), &[String::from("lib_1.cfg_testb*")], &libraries_toml_path, &tool_toml_path, &replacements, &BindingOverrides::new());
+        assert_eq!(element_list.last().map(|el| el.unit.as_str()), Some("cfg_testbench_1"));
+
+        // testbenches with the given architectures of design_1 analyzed, the last one is bound by default
+        let testbench = |archs: &[(&str, &str)]| {
+            let mut ret: Vec<Element> = archs.iter().map(|(filename, arch)| Element::new("lib_1", filename, "vhdl", &format!("design_1({})", arch))).collect();
+            let mut tb = Element::new("lib_1", "tb.vhd", "vhdl", "tb(sim)");
+            let mut dep = ret.last().unwrap().as_dependency("instance", "dut_i");
+            dep.binding = String::from("default");
+            tb.depends_on.push(dep);
+            ret.push(tb);
+            ret
+        };
+        let tb_a = testbench(&[("a.vhd", "rtl")]);
+        let tb_b = testbench(&[("b.vhd", "fast")]);
+        let tb_c = testbench(&[("a.vhd", "rtl"), ("b.vhd", "fast")]);
+        let lists = vec![(String::from("tb_a"), tb_a), (String::from("tb_b"), tb_b)];
+        assert_eq!(get_architecture_conflicts(&lists), vec!["lib_1.design_1 (tb_a: rtl; tb_b: fast; analyzed last: fast)"]);
+        let tb_b = lists.into_iter().nth(1).unwrap().1;
+        // fast stays the last analyzed architecture, so no default binding changes
+        let lists = vec![(String::from("tb_c"), tb_c.iter().map(|el| el.copy()).collect()), (String::from("tb_b"), tb_b.iter().map(|el| el.copy()).collect())];
+        assert!(get_architecture_conflicts(&lists).is_empty());
+        // rtl is analyzed after fast, tb_b and tb_c would get rtl instead of fast
+        let lists = vec![(String::from("tb_b"), tb_b), (String::from("tb_c"), tb_c)];
+        assert_eq!(get_architecture_conflicts(&lists), vec!["lib_1.design_1 (tb_b: fast; tb_c: fast; analyzed last: rtl)"]);
+
+        // configurations binding different architectures don't depend on the order
+        let dir = std::env::temp_dir().join("hanna_test_conflicts");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let configuration = |name: &str, arch: &str| {
+            format!("configuration {} of top is\n  for sim\n    for e_i : e\n      use entity work.e({});\n    end for;\n  end for;\nend configuration;\n", name, arch)
+        };
+        let sources = [
+            ("e.vhd", String::from("entity e is\nend entity;\narchitecture rtl of e is\nbegin\nend architecture;\n")),
+            ("e_fast.vhd", String::from("architecture fast of e is\nbegin\nend architecture;\n")),
+            ("top.vhd", String::from("entity top is\nend entity;\narchitecture sim of top is\n  component e is\n  end component;\nbegin\n  e_i : e;\nend architecture;\n")),
+            ("cfg_rtl.vhd", configuration("cfg_rtl", "rtl")),
+            ("cfg_fast.vhd", configuration("cfg_fast", "fast")),
+        ];
+        for (filename, source) in &sources {
+            fs::write(dir.join(filename), source).unwrap();
+        }
+        let libraries_toml_path = String::from(dir.join("libraries.toml").to_str().unwrap());
+        fs::write(&libraries_toml_path, format!("[lib_a]\nvhdl = [\"{}/*.vhd\"]\n", dir.display())).unwrap();
+        let libraries = get_libraries(&libraries_toml_path, &tool_toml_path, &replacements);
+        let list = |top: &str, overrides: &BindingOverrides| (top.to_string(), get_toplevel_elements(&libraries, &String::from("lib_a"), &top.to_string(), overrides));
+        let lists = vec![list("cfg_rtl", &BindingOverrides::new()), list("cfg_fast", &BindingOverrides::new())];
+        assert!(lists.iter().all(|(_, element_list)| !element_list.is_empty()));
+        assert!(get_architecture_conflicts(&lists).is_empty());
+        // the same architectures selected with --arch are bound by default
+        let arch = |arch: &str| BindingOverrides { archs: vec![(String::from("e"), arch.to_string())], ..BindingOverrides::new() };
+        let lists = vec![list("top(sim)", &arch("rtl")), list("top(sim)", &arch("fast"))];
+        assert_eq!(get_architecture_conflicts(&lists).len(), 1);
+    }
+
+    #[test]
+    fn test_all_libraries() {
+        let libraries_toml_path = String::from("tomls/libraries.toml");
+        let tool_toml_path = String::from("tomls/tools/echo.toml");
+        let replacements: HashMap<String, String> = HashMap::new();
+        let (element_list, libraries) = get_element_list(String::new(), &[], &libraries_toml_path, &tool_toml_path, &replacements, &BindingOverrides::new());
+        let order = get_compile_order(&element_list, &get_sorted_libraries(&libraries));
+        let lib_1 = &order.iter().find(|l| l.library == "lib_1").unwrap().files;
+        let position = |name: &str| lib_1.iter().position(|f| f.ends_with(name)).unwrap();
+        // package_1 isn't needed by any toplevel, but belongs to the library
+        assert_eq!(lib_1.len(), 6);
+        assert!(position("/design_1.vhd") < position("design_1_rtl.vhd"));
+        assert!(position("design_1_rtl.vhd") < position("/cfg_design_1.vhd"));
+        assert!(position("testbench.vhd") < position("cfg_testbench_1.vhd"));
+        assert!(lib_1.iter().any(|f| f.ends_with("package_1.vhd")));
+    }
+
+    #[test]
+    fn test_unused() {
+        let libraries_toml_path = String::from("tomls/libraries.toml");
+        let tool_toml_path = String::from("tomls/tools/echo.toml");
+        let replacements: HashMap<String, String> = HashMap::new();
+        let (element_list, libraries) = get_element_list(String::from("lib_1"), &[String::from("lib_1.cfg_testbench_1")], &libraries_toml_path, &tool_toml_path, &replacements, &BindingOverrides::new());
+        let files = get_unused_files(&libraries, &element_list);
+        let lib_1: Vec<&String> = files.iter().filter(|(lib, _)| lib == "lib_1").map(|(_, f)| f).collect();
+        assert_eq!(lib_1.len(), 1);
+        assert!(lib_1[0].ends_with("package_1.vhd"));
+        assert_eq!(files.iter().filter(|(lib, _)| lib == "lib_2").count(), 6);
+        let units = get_unreferenced_units(&libraries);
+        let lib_1: Vec<(&str, &str)> = units.iter().filter(|(lib, _, _)| lib == "lib_1").map(|(_, u, k)| (u.as_str(), k.as_str())).collect();
+        assert_eq!(lib_1, vec![("package_1_pkg", "package"), ("cfg_testbench_1", "configuration")]);
+    }
+
+    #[test]
+    fn test_roots() {
+        let libraries_toml_path = String::from("tomls/libraries.toml");
+        let tool_toml_path = String::from("tomls/tools/echo.toml");
+        let replacements: HashMap<String, String> = HashMap::new();
+        let libraries = get_libraries(&libraries_toml_path, &tool_toml_path, &replacements);
+        let roots = get_roots(&libraries, "lib_1");
+        let units: Vec<(&str, bool, bool)> = roots.iter().map(|r| (r.unit.as_str(), r.testbench, r.preferred)).collect();
+        // design_1 is instantiated through cfg_design_1
+        assert_eq!(units, vec![("testbench(structure)", true, false), ("cfg_testbench_1", true, true)]);
+        assert!(roots.iter().all(|r| r.library == "lib_1"));
+        assert_eq!(get_roots(&libraries, "").len(), 4);
+        // alu is instantiated by cfg_top, so its configuration isn't preferred
+        let libraries = get_libraries(&String::from("tomls/libraries_cfg.toml"), &tool_toml_path, &replacements);
+        let roots = get_roots(&libraries, "lib_cfg");
+        let units: Vec<(&str, bool)> = roots.iter().map(|r| (r.unit.as_str(), r.preferred)).collect();
+        assert_eq!(units, vec![("cfg_top(sim)", true), ("cfg_alu", false)]);
+    }
+
+    #[test]
+    fn test_runner() {
+        let libraries_toml_path = String::from("tomls/libraries.toml");
+        let tool_toml_path = String::from("tomls/tools/echo_test.toml");
+        let test_dir = std::env::temp_dir().join("hanna_test_runner");
+        let junit = test_dir.join("junit.xml");
+        let mut replacements: HashMap<String, String> = HashMap::new();
+        replacements.insert(String::from("test_dir"), String::from(test_dir.to_str().unwrap()));
+        replacements.insert(String::from("run_extra"), String::from("echo done"));
+        let options = TestOptions {
+            pattern: String::from("lib_1.cfg_*"),
+            junit: String::from(junit.to_str().unwrap()),
+            ..TestOptions::new()
+        };
+        assert_eq!(run_tests(&libraries_toml_path, &tool_toml_path, &replacements, &BindingOverrides::new(), &options), 0);
+        assert!(test_dir.join("lib_1.cfg_testbench_1").is_dir());
+        let content = std::fs::read_to_string(&junit).unwrap();
+        assert!(content.contains("tests=\"1\" failures=\"0\""));
+        assert!(content.contains("<testcase classname=\"lib_1\" name=\"cfg_testbench_1\""));
+
+        replacements.insert(String::from("run_extra"), String::from("echo 'FAILURE: x < y'"));
+        assert_eq!(run_tests(&libraries_toml_path, &tool_toml_path, &replacements, &BindingOverrides::new(), &options), 1);
+        let content = std::fs::read_to_string(&junit).unwrap();
+        assert!(content.contains("<failure message=\"output matches 'FAILURE': FAILURE: x &lt; y\"/>"));
+    }
+
+    #[test]
+    fn test_runner_timeout() {
+        let tool_toml_path = String::from("tomls/tools/timeout.toml");
+        let run_dir = std::env::temp_dir().join("hanna_test_timeout");
+        let mut tool_config = read_tool_toml(&tool_toml_path, &HashMap::new());
+        tool_config.test.run_dir = String::from(run_dir.to_str().unwrap());
+        let result = run_test(&tool_config, "lib_1.cfg_testbench_1", &[]);
+        assert_eq!(result.status, "failed");
+        assert_eq!(result.message, "timeout after 1s");
+        assert!(result.duration.as_secs() < 10);
+        // the process started in the background is killed together with its shell
+        let pid = fs::read_to_string(run_dir.join("sleep.pid")).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        let ps = std::process::Command::new("ps").args(["-o", "stat=", "-p", pid.trim()]).output().unwrap();
+        let state = String::from_utf8_lossy(&ps.stdout);
+        assert!(state.trim().is_empty() || state.trim().starts_with('Z'), "sleep is still running: {}", state);
+    }
+
+    #[test]
+    fn test_generics() {
+        let libraries_toml_path = String::from("tomls/libraries.toml");
+        let tool_toml_path = String::from("tomls/tools/echo_generics.toml");
+        let test_dir = std::env::temp_dir().join("hanna_test_generics");
+        let mut replacements: HashMap<String, String> = HashMap::new();
+        replacements.insert(String::from("test_dir"), String::from(test_dir.to_str().unwrap()));
+
+        let script_filename = String::from(test_dir.join("generics.sh").to_str().unwrap());
+        std::fs::create_dir_all(&test_dir).unwrap();
+        let generics = vec![(String::from("WIDTH"), String::from("4"))];
+        gen_script(String::from("lib_1"), &[String::from("lib_1.cfg_testbench_1")], &libraries_toml_path, &tool_toml_path, &replacements, &script_filename, &ScriptOptions { generics, ..ScriptOptions::new() });
+        let content = std::fs::read_to_string(&script_filename).unwrap();
+        assert!(content.contains("echo run lib_1.cfg_testbench_1 -GWIDTH=4"));
+
+        let options = TestOptions {
+            pattern: String::from("lib_1.*"),
+            junit: String::from(test_dir.join("junit.xml").to_str().unwrap()),
+            generics: vec![(String::from("seed"), String::from("5"))],
+            ..TestOptions::new()
+        };
+        assert_eq!(run_tests(&libraries_toml_path, &tool_toml_path, &replacements, &BindingOverrides::new(), &options), 0);
+        let content = std::fs::read_to_string(test_dir.join("junit.xml")).unwrap();
+        assert!(content.contains("tests=\"2\" failures=\"0\""));
+        assert!(content.contains("name=\"cfg_testbench_1[WIDTH=8,seed=5]\""));
+        assert!(content.contains("name=\"cfg_testbench_1[SEED=5,WIDTH=16]\""));
+        assert!(test_dir.join("lib_1.cfg_testbench_1/WIDTH_8-seed_5").is_dir());
+    }
+
+    #[test]
+    fn test_binding_overrides() {
+        let libraries_toml_path = String::from("tomls/libraries_bind.toml");
+        let tool_toml_path = String::from("tomls/tools/echo.toml");
+        let replacements: HashMap<String, String> = HashMap::new();
+        let (element_list, _) = get_element_list(String::from("lib_bind"), &[String::from("lib_bind.top_bind(sim)")], &libraries_toml_path, &tool_toml_path, &replacements, &BindingOverrides::new());
+        let lines = tree_lines(&get_instance_tree(&get_hierarchy(&element_list)));
+        assert!(lines.contains(&String::from("├── model_i: lib_bind.model (unresolved)")));
+
+        let overrides = BindingOverrides {
+            binds: vec![(String::from("dut_i"), String::from("lib_bind.model(behav)"))],
+            archs: vec![(String::from("model"), String::from("rtl"))],
+            ..BindingOverrides::new()
+        };
+        let (element_list, _) = get_element_list(String::from("lib_bind"), &[String::from("lib_bind.top_bind(sim)")], &libraries_toml_path, &tool_toml_path, &replacements, &overrides);
+        // the overrides are a parameter, so they are kept on other threads
+        let units = |element_list: &Vec<Element>| element_list.iter().map(|el| el.unit.clone()).collect::<Vec<String>>();
+        let (paths, thread_overrides) = ((libraries_toml_path.clone(), tool_toml_path.clone()), overrides.clone());
+        let thread_units = std::thread::spawn(move || {
+            let (element_list, _) = get_element_list(String::from("lib_bind"), &[String::from("lib_bind.top_bind(sim)")], &paths.0, &paths.1, &HashMap::new(), &thread_overrides);
+            units(&element_list)
+        })
+        .join()
+        .unwrap();
+        assert_eq!(thread_units, units(&element_list));
+        let lines = tree_lines(&get_instance_tree(&get_hierarchy(&element_list)));
+        assert!(lines.contains(&String::from("├── model_i: lib_bind.model(rtl) [vhdl] (override)")));
+        assert!(lines.contains(&String::from("└── dut_i: lib_bind.model(behav) [vhdl] (override)")));
+        // dependencies of the overriding architecture are compiled too
+        assert!(element_list.iter().any(|el| el.unit == "helper(rtl)"));
+    }
+
+    #[test]
+    fn test_gen_config() {
+        let libraries_toml_path = String::from("tomls/libraries_cfg.toml");
+        let tool_toml_path = String::from("tomls/tools/echo.toml");
+        let replacements: HashMap<String, String> = HashMap::new();
+        let (element_list, libraries) = get_element_list(String::from("lib_cfg"), &[String::from("lib_cfg.cfg_top(sim)")], &libraries_toml_path, &tool_toml_path, &replacements, &BindingOverrides::new());
+        let lines = get_configuration(&get_hierarchy(&element_list), &libraries, "cfg_x");
+        let expected = [
+            "-- configuration of lib_cfg.cfg_top(sim) generated by hanna",
+            "library lib_cfg;",
+            "",
+            "configuration cfg_x of cfg_top is",
+            "  for sim",
+            "    for gen_alu",
+            "      for alu_i : alu",
+            "        use entity lib_cfg.alu(rtl);",
+            "        for rtl",
+            "          for gen_add",
+            "            for add_i : adder",
+            "              use entity lib_cfg.adder(rtl);",
+            "            end for;",
+            "          end for;",
+            "        end for;",
+            "      end for;",
+            "    end for;",
+            "    for reg_i : reg",
+            "    end for;",
+            "    for blk_alu",
+            "      for alu2_i : alu",
+            "        use entity lib_cfg.alu(rtl);",
+            "        for rtl",
+            "          for gen_add",
+            "            for add_i : adder",
+            "              use entity lib_cfg.adder(rtl);",
+            "            end for;",
+            "          end for;",
+            "        end for;",
+            "      end for;",
+            "    end for;",
+            "    -- mem_i: lib_cfg.mem(rtl) is instantiated directly and can't be configured",
+            "  end for;",
+            "end configuration cfg_x;",
+        ];
+        assert_eq!(lines, expected);
+        // hanna reads the nested component configurations back
+        let content = lines.join("\n");
+        let labels: Vec<&str> = RE_CONF_COMP_SPEC.captures_iter(&content).map(|c| c.name("label").unwrap().as_str()).collect();
+        assert_eq!(labels, ["alu_i", "add_i", "alu2_i", "add_i"]);
+    }
+
+    #[test]
+    fn test_check() {
+        let libraries_toml_path = String::from("tomls/libraries_check.toml");
+        let tool_toml_path = String::from("tomls/tools/echo.toml");
+        let replacements: HashMap<String, String> = HashMap::new();
+        let libraries = get_libraries(&libraries_toml_path, &tool_toml_path, &replacements);
+        let findings: Vec<(String, usize, String)> = check_configurations(&libraries, "lib_check")
+            .into_iter()
+            .map(|f| (f.unit, f.line, f.message))
+            .collect();
+        let expected = [
+            ("cfg_chk_labels", 5, "label alu_x doesn't exist in lib_check.chk_top(sim)"),
+            ("cfg_chk_labels", 8, "reg_i in lib_check.chk_top(sim) is an instance of component reg, not alu"),
+            ("cfg_chk_labels", 11, "mem_i in lib_check.chk_top(sim) instantiates entity mem directly and can't be configured"),
+            ("cfg_chk_labels", 4, "instance alu_i of component alu in lib_check.chk_top(sim) isn't configured"),
+            ("cfg_chk_labels", 4, "instance reg2_i of component reg in lib_check.chk_top(sim) isn't configured"),
+            ("cfg_chk_ports", 5, "component alu doesn't match entity lib_cfg.adder: port a is bit in the component but std_logic in the entity"),
+            ("cfg_chk_units", 5, "architecture fast of lib_cfg.alu doesn't exist"),
+            ("cfg_chk_units", 8, "configuration lib_cfg.cfg_reg doesn't exist"),
+            ("cfg_chk_units", 11, "entity lib_cfg.regx doesn't exist"),
+        ];
+        let expected: Vec<(String, usize, String)> = expected
+            .iter()
+            .map(|(c, l, m)| (c.to_string(), *l, m.to_string()))
+            .collect();
+        // cfg_chk_ok, including its nested block configurations, is consistent
+        assert_eq!(findings, expected);
+
+        let ports: Vec<String> = libraries["lib_check"].designs["shifter"]
+            .entity
+            .ports
+            .iter()
+            .map(|p| format!("{} {} {} {}", p.name, p.mode, p.typ, p.default))
+            .collect();
+        assert_eq!(ports, ["a in std_logic_vector(width - 1 downto 0) ", "z out std_logic ", "en in std_logic '1'"]);
+        let findings: Vec<(String, usize, String)> = check_components(&libraries, "lib_check")
+            .into_iter()
+            .map(|f| (f.unit, f.line, f.message))
+            .collect();
+        let expected = [
+            ("chk_ports(sim)", 21, "component alu doesn't match entity lib_cfg.alu: port a is bit in the component but std_logic in the entity"),
+            ("chk_ports(sim)", 31, "component shifter doesn't match entity lib_check.shifter: generic depth is missing in the component"),
+            ("chk_ports(sim)", 31, "component shifter doesn't match entity lib_check.shifter: port z is in in the component but out in the entity"),
+            ("chk_ports(sim)", 31, "component shifter doesn't match entity lib_check.shifter: port q doesn't exist in the entity"),
+            // bound by configuration specifications, shifter matches lib_cfg.alu
+            ("chk_spec(sim)", 28, "component alu doesn't match entity lib_cfg.adder: port a is bit in the component but std_logic in the entity"),
+        ];
+        let expected: Vec<(String, usize, String)> = expected
+            .iter()
+            .map(|(u, l, m)| (u.to_string(), *l, m.to_string()))
+            .collect();
+        assert_eq!(findings, expected);
+    }
+
+    #[test]
+    fn test_parser() {
+        let filename = std::env::temp_dir().join("hanna_test_parser.vhd");
+        let filename = String::from(filename.to_str().unwrap());
+        let content = [
+            "entity top is",
+            "end entity top;",
+            "architecture sim of top is",
+            "  component sub is",
+            "    port (a : in bit);",
+            "  end component;",
+            "begin",
+            "  blk : block",
+            "  begin",
+            "    sub_i : sub",
+            "      port map (a => '0');",
+            "  end block blk;",
+            "  leaf_i : entity work.leaf(rtl);",
+            "end architecture sim;",
+            "configuration cfg_top of top is",
+            "  for sim",
+            "    for blk",
+            "      for sub_i : sub",
+            "        use entity work.sub(rtl);",
+            "        for rtl",
+            "          for leaf_i : leaf",
+            "            use configuration work.cfg_leaf;",
+            "          end for;",
+            "        end for;",
+            "      end for;",
+            "    end for;",
+            "  end for;",
+            "end configuration cfg_top;",
+        ];
+        fs::write(&filename, content.join("\n")).unwrap();
+        let pre_worked = pre_work_file_content(&content.join("\n"));
+        assert!(!pre_worked.contains("block"));
+        let mut library = Library::new();
+        library.name = String::from("lib_p");
+        library.analyze_vhdl_file(&filename);
+        // the instance inside the labelled block and the one after it are found
+        let instances: Vec<(&str, &str, &str)> = library.designs["top"].architectures["sim"]
+            .instances
+            .iter()
+            .map(|i| (i.label.as_str(), i.typ.as_str(), i.component.as_str()))
+            .collect();
+        assert_eq!(instances, [("sub_i", "component", "sub"), ("leaf_i", "entity", "leaf(rtl)")]);
+        // the component configuration and the one of its nested block configuration
+        let conf = &library.configurations["cfg_top"];
+        assert_eq!(conf.architecture, "sim");
+        let mut instances: Vec<(&str, &str, &str, &str)> = conf
+            .instances
+            .values()
+            .map(|i| (i.label.as_str(), i.library.as_str(), i.typ.as_str(), i.component.as_str()))
+            .collect();
+        instances.sort();
+        assert_eq!(instances, [("leaf_i", "work", "configuration", "cfg_leaf"), ("sub_i", "work", "entity", "sub(rtl)")]);
     }
 
     #[test]
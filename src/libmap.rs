@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+use std::fs;
+use std::process::exit;
+
+use crate::classes::ScriptOptions;
+use crate::{get_element_list, get_sorted_libraries};
+
+// physical directory of a library from a pattern like 'work/{library}'
+pub fn get_library_dir(lib_dir: &str, lib_name: &str) -> String {
+    lib_dir.replace("{library}", lib_name)
+}
+
+fn write_file(filename: &str, content: Vec<String>) {
+    match fs::write(filename, content.join("\n") + "\n") {
+        Ok(_) => println!("{} was written!", filename),
+        Err(err) => {
+            eprintln!("Can't write to '{}'\n{}", filename, err);
+            exit(1);
+        }
+    }
+}
+
+pub fn modelsim_ini_content(libs: &[(String, String)]) -> Vec<String> {
+    let mut content: Vec<String> = vec![String::from("[Library]")];
+    content.push(String::from("others = $MODEL_TECH/../modelsim.ini"));
+    for (lib_name, dir) in libs {
+        content.push(format!("{} = {}", lib_name, dir));
+    }
+    content
+}
+
+pub fn cds_lib_content(libs: &[(String, String)]) -> Vec<String> {
+    let mut content: Vec<String> = vec![String::from("SOFTINCLUDE $CDS_INST_DIR/tools/inca/files/cds.lib")];
+    for (lib_name, dir) in libs {
+        content.push(format!("DEFINE {} {}", lib_name, dir));
+    }
+    content
+}
+
+pub fn synopsys_sim_setup_content(libs: &[(String, String)], work: &str) -> Vec<String> {
+    let mut content: Vec<String> = vec![String::from("OTHERS = $VCS_HOME/bin/synopsys_sim.setup")];
+    content.push(format!("WORK > {}", work));
+    for (lib_name, dir) in libs {
+        content.push(format!("{} : {}", lib_name, dir));
+    }
+    content
+}
+
+pub fn write_libmap(
+    lib_name: String, toplevels: &[String], libraries_toml_filename: &String, tool_toml_filename: &String,
+    replacements: &HashMap<String, String>, path: &str, options: &ScriptOptions,
+) {
+    let (element_list, libraries) = get_element_list(lib_name.clone(), toplevels, libraries_toml_filename, tool_toml_filename, replacements, &options.overrides);
+    let lib_order = get_sorted_libraries(&libraries);
+    if element_list.is_empty() {
+        eprintln!("No files found to write out!");
+        exit(1);
+    }
+    let mut l_path = String::from(path.strip_suffix('/').unwrap_or(path));
+    l_path.push('/');
+    let mut libs: Vec<(String, String)> = Vec::new();
+    // only the precompiled libraries the toplevels use, their units aren't in the element list
+    let mut precompiled: Vec<(String, String)> = libraries
+        .values()
+        .filter(|l| !l.precompiled.is_empty())
+        .filter(|l| element_list.iter().flat_map(|el| &el.depends_on).any(|dep| dep.library == l.name))
+        .map(|l| (l.name.clone(), l.precompiled.clone()))
+        .collect();
+    precompiled.sort();
+    for name in &lib_order {
+        if element_list.iter().any(|el| &el.library == name) {
+            libs.push((name.clone(), get_library_dir(&options.lib_dir, name)));
+        }
+    }
+    // the simulators expect the library directories to exist
+    for (_, dir) in &libs {
+        let dir = if dir.starts_with('/') { dir.clone() } else { format!("{}{}", l_path, dir) };
+        if let Err(err) = fs::create_dir_all(&dir) {
+            eprintln!("Can't create directory '{}'\n{}", dir, err);
+            exit(1);
+        }
+    }
+    // precompiled libraries come first, their directories exist already
+    libs.splice(0..0, precompiled);
+    let all = options.format == "all";
+    if all || options.format == "modelsim" {
+        write_file(&format!("{}modelsim.ini", l_path), modelsim_ini_content(&libs));
+    }
+    if all || options.format == "cadence" {
+        write_file(&format!("{}cds.lib", l_path), cds_lib_content(&libs));
+        write_file(&format!("{}hdl.var", l_path), vec![format!("DEFINE WORK {}", lib_name)]);
+    }
+    if all || options.format == "synopsys" {
+        write_file(&format!("{}synopsys_sim.setup", l_path), synopsys_sim_setup_content(&libs, &lib_name));
+    }
+}
@@ -6,8 +6,21 @@ use clap::{CommandFactory, Parser};
 use env_logger::Target;
 use log::{debug, error, warn};
 
-use hanna::{gen_script, get_library_names_from_toml, get_toplevels_from_lib, print_help_toml, run_script, write_json_file, write_lib_lists};
-use hanna::classes::RE_ENT;
+use hanna::{execute, gen_script, get_library_names_from_toml, get_toplevels_from_lib, print_help_toml, print_json_schema, write_json_file, write_lib_lists};
+use hanna::affected::print_affected;
+use hanna::classes::{BindingOverrides, RE_ENT, ScriptOptions, TestOptions, TreeOptions};
+use hanna::check::print_check;
+use hanna::gen_config::write_configuration;
+use hanna::graph::print_graph;
+use hanna::libmap::write_libmap;
+use hanna::makefile::write_makefile;
+use hanna::ninja::write_ninja_file;
+use hanna::roots::print_roots;
+use hanna::test_runner::run_tests;
+use hanna::tree::print_tree;
+use hanna::unused::print_unused;
+use hanna::vhdl_ls::write_vhdl_ls_toml;
+use hanna::why::print_why;
 
 use crate::cli::ArgsStruct;
 use crate::cli::Cli;
@@ -47,52 +60,254 @@ fn main() {
                         libraries: fc.libraries,
                         tool: fc.tool,
                         replacement: fc.replacement,
-                        filename: String::new(),
-                        forces: Vec::new(),
+                        roots: fc.roots,
+                        json: fc.json,
+                        ..Default::default()
                     }
                 }
                 Commands::Files(fc) => {
                     args = ArgsStruct {
                         command: "files".to_string(),
-                        toplevel: fc.toplevel,
+                        binds: fc.bind,
+                        archs: fc.arch,
+                        toplevel: fc.toplevel.first().cloned().unwrap_or_default(),
+                        toplevels: fc.toplevel,
+                        all: fc.all,
                         libraries: fc.libraries,
                         tool: fc.tool,
                         replacement: fc.replacement,
                         filename: fc.path,
                         forces: fc.force,
+                        ..Default::default()
                     }
                 }
                 Commands::Json(jc) => {
+                    if jc.schema {
+                        print_json_schema();
+                        exit(0)
+                    }
                     args = ArgsStruct {
                         command: "json".to_string(),
-                        toplevel: jc.toplevel,
+                        binds: jc.bind,
+                        archs: jc.arch,
+                        toplevel: jc.toplevel.first().cloned().unwrap_or_default(),
+                        toplevels: jc.toplevel,
+                        all: jc.all,
                         libraries: jc.libraries,
                         tool: jc.tool,
                         replacement: jc.replacement,
                         filename: jc.name,
                         forces: jc.force,
+                        ..Default::default()
                     }
                 }
                 Commands::Script(sc) => {
                     args = ArgsStruct {
                         command: "script".to_string(),
-                        toplevel: sc.toplevel,
+                        binds: sc.bind,
+                        archs: sc.arch,
+                        toplevel: sc.toplevel.first().cloned().unwrap_or_default(),
+                        toplevels: sc.toplevel,
+                        all: sc.all,
                         libraries: sc.libraries,
                         tool: sc.tool,
                         replacement: sc.replacement,
                         filename: sc.name,
                         forces: sc.force,
+                        stop_after: sc.stop_after,
+                        generics: sc.generic,
+                        parallel: sc.parallel,
+                        format: sc.format,
+                        vhdl_std: sc.vhdl_std,
+                        lib_dir: sc.lib_dir,
+                        ..Default::default()
                     }
                 }
-                Commands::Execute(sc) => {
+                Commands::Execute(ec) => {
                     args = ArgsStruct {
                         command: "execute".to_string(),
-                        toplevel: sc.toplevel,
-                        libraries: sc.libraries,
-                        tool: sc.tool,
-                        replacement: sc.replacement,
-                        filename: sc.name,
-                        forces: sc.force,
+                        binds: ec.bind,
+                        archs: ec.arch,
+                        toplevel: ec.toplevel,
+                        libraries: ec.libraries,
+                        tool: ec.tool,
+                        replacement: ec.replacement,
+                        forces: ec.force,
+                        stop_after: ec.stop_after,
+                        generics: ec.generic,
+                        jobs: ec.jobs,
+                        full: ec.full,
+                        stamps_dir: ec.stamps,
+                        ..Default::default()
+                    }
+                }
+                Commands::Make(mc) => {
+                    args = ArgsStruct {
+                        command: "make".to_string(),
+                        binds: mc.bind,
+                        archs: mc.arch,
+                        toplevel: mc.toplevel,
+                        libraries: mc.libraries,
+                        tool: mc.tool,
+                        replacement: mc.replacement,
+                        filename: mc.name,
+                        forces: mc.force,
+                        stamps_dir: mc.stamps,
+                        ..Default::default()
+                    }
+                }
+                Commands::Libmap(lc) => {
+                    args = ArgsStruct {
+                        command: "libmap".to_string(),
+                        binds: lc.bind,
+                        archs: lc.arch,
+                        toplevel: lc.toplevel,
+                        libraries: lc.libraries,
+                        tool: lc.tool,
+                        replacement: lc.replacement,
+                        filename: lc.path,
+                        forces: lc.force,
+                        format: lc.format,
+                        lib_dir: lc.lib_dir,
+                        ..Default::default()
+                    }
+                }
+                Commands::VhdlLs(vc) => {
+                    args = ArgsStruct {
+                        command: "vhdl-ls".to_string(),
+                        binds: vc.bind,
+                        archs: vc.arch,
+                        toplevel: vc.toplevel.unwrap_or_default(),
+                        libraries: vc.libraries,
+                        tool: vc.tool,
+                        replacement: vc.replacement,
+                        filename: vc.name,
+                        ..Default::default()
+                    }
+                }
+                Commands::Tree(tc) => {
+                    args = ArgsStruct {
+                        command: "tree".to_string(),
+                        binds: tc.bind,
+                        archs: tc.arch,
+                        toplevel: tc.toplevel,
+                        libraries: tc.libraries,
+                        tool: tc.tool,
+                        replacement: tc.replacement,
+                        depth: tc.depth,
+                        library_filter: tc.library,
+                        json: tc.json,
+                        ..Default::default()
+                    }
+                }
+                Commands::Graph(gc) => {
+                    args = ArgsStruct {
+                        command: "graph".to_string(),
+                        binds: gc.bind,
+                        archs: gc.arch,
+                        toplevel: gc.toplevel,
+                        libraries: gc.libraries,
+                        tool: gc.tool,
+                        replacement: gc.replacement,
+                        kind: gc.kind,
+                        format: gc.format,
+                        ..Default::default()
+                    }
+                }
+                Commands::Why(wc) => {
+                    args = ArgsStruct {
+                        command: "why".to_string(),
+                        binds: wc.bind,
+                        archs: wc.arch,
+                        toplevel: wc.toplevel,
+                        libraries: wc.libraries,
+                        tool: wc.tool,
+                        replacement: wc.replacement,
+                        target: wc.target,
+                        ..Default::default()
+                    }
+                }
+                Commands::Affected(ac) => {
+                    args = ArgsStruct {
+                        command: "affected".to_string(),
+                        libraries: ac.libraries,
+                        tool: ac.tool,
+                        replacement: ac.replacement,
+                        changed: ac.changed,
+                        git_diff: ac.git_diff.unwrap_or_default(),
+                        json: ac.json,
+                        ..Default::default()
+                    }
+                }
+                Commands::Test(tc) => {
+                    args = ArgsStruct {
+                        command: "test".to_string(),
+                        binds: tc.bind,
+                        archs: tc.arch,
+                        libraries: tc.libraries,
+                        tool: tc.tool,
+                        replacement: tc.replacement,
+                        pattern: tc.pattern,
+                        timeout: tc.timeout,
+                        fail_patterns: tc.fail_pattern,
+                        junit: tc.junit,
+                        jobs: tc.jobs,
+                        generics: tc.generic,
+                        ..Default::default()
+                    }
+                }
+                Commands::Check(cc) => {
+                    args = ArgsStruct {
+                        command: "check".to_string(),
+                        toplevel: cc.library,
+                        libraries: cc.libraries,
+                        tool: cc.tool,
+                        replacement: cc.replacement,
+                        json: cc.json,
+                        ..Default::default()
+                    }
+                }
+                Commands::GenConfig(gc) => {
+                    args = ArgsStruct {
+                        command: "gen-config".to_string(),
+                        binds: gc.bind,
+                        archs: gc.arch,
+                        toplevel: gc.toplevel,
+                        libraries: gc.libraries,
+                        tool: gc.tool,
+                        replacement: gc.replacement,
+                        config_name: gc.name,
+                        filename: gc.output,
+                        ..Default::default()
+                    }
+                }
+                Commands::Unused(uc) => {
+                    args = ArgsStruct {
+                        command: "unused".to_string(),
+                        binds: uc.bind,
+                        archs: uc.arch,
+                        toplevel: uc.toplevel.first().cloned().unwrap_or_default(),
+                        toplevels: uc.toplevel,
+                        libraries: uc.libraries,
+                        tool: uc.tool,
+                        replacement: uc.replacement,
+                        json: uc.json,
+                        ..Default::default()
+                    }
+                }
+                Commands::Ninja(nc) => {
+                    args = ArgsStruct {
+                        command: "ninja".to_string(),
+                        binds: nc.bind,
+                        archs: nc.arch,
+                        toplevel: nc.toplevel,
+                        libraries: nc.libraries,
+                        tool: nc.tool,
+                        replacement: nc.replacement,
+                        filename: nc.name,
+                        forces: nc.force,
+                        stamps_dir: nc.stamps,
+                        ..Default::default()
                     }
                 }
             };
@@ -119,7 +334,8 @@ fn main() {
     //let libraries = read_libraries_toml(&args.libraries, &replacements, &tool_toml);
     let lib_name: String;
 
-    if args.command == "info" {
+    // info and check take a library, vhdl-ls and affected take all of them without a toplevel
+    if args.command == "info" || args.command == "check" || args.toplevel.is_empty() {
         lib_name = args.toplevel.clone();
     } else {
         match RE_ENT.captures(&args.toplevel) {
@@ -142,9 +358,84 @@ fn main() {
         }
     };
 
+    let mut toplevels: Vec<String> = if !args.toplevels.is_empty() || args.toplevel.is_empty() {
+        args.toplevels.clone()
+    } else {
+        vec![args.toplevel.clone()]
+    };
+
+    // files, json and script compile everything only if asked to
+    if ["files", "json", "script"].contains(&args.command.as_str()) {
+        if args.all {
+            toplevels.clear();
+        } else if toplevels.iter().all(|t| t.trim().is_empty()) {
+            error!("A toplevel or --all is needed");
+            exit(1)
+        }
+    }
+
+    let mut generics: Vec<(String, String)> = Vec::new();
+    for generic in &args.generics {
+        match generic.split_once('=') {
+            Some((name, value)) if !name.trim().is_empty() => {
+                generics.push((name.trim().to_string(), value.to_string()));
+            }
+            _ => {
+                error!("generic '{}' must be of form name=value", generic);
+                exit(1)
+            }
+        }
+    }
+
+    for pattern in &args.fail_patterns {
+        if let Err(err) = regex::Regex::new(pattern) {
+            error!("fail pattern '{}' is no valid regex: {}", pattern, err);
+            exit(1)
+        }
+    }
+
+    let mut overrides = BindingOverrides::new();
+    for bind in &args.binds {
+        match bind.split_once('=') {
+            Some((path, target)) if !path.is_empty() && RE_ENT.captures(target).is_some_and(|c| c.name("lib").is_some()) => {
+                overrides.binds.push((path.to_string(), target.to_string()));
+            }
+            _ => {
+                error!("binding '{}' must be of form label.path=lib.entity(arch) or label.path=lib.configuration", bind);
+                exit(1)
+            }
+        }
+    }
+    for arch in &args.archs {
+        match arch.split_once('=') {
+            Some((entity, name)) if !entity.is_empty() && !name.is_empty() => {
+                overrides.archs.push((entity.to_string(), name.to_string()));
+            }
+            _ => {
+                error!("architecture '{}' must be of form entity=arch or lib.entity=arch", arch);
+                exit(1)
+            }
+        }
+    }
+
+    let options = ScriptOptions {
+        stop_after: args.stop_after.clone(),
+        parallel: args.parallel,
+        jobs: args.jobs,
+        full: args.full,
+        stamps_dir: args.stamps_dir.clone(),
+        format: args.format.clone(),
+        vhdl_std: args.vhdl_std.clone(),
+        lib_dir: args.lib_dir.clone(),
+        generics: generics.clone(),
+        overrides: overrides.clone(),
+    };
+
     match args.command.as_str() {
         "info" => {
-            if lib_name.is_empty() {
+            if args.roots {
+                print_roots(&lib_name, &args.libraries, &args.tool, &replacements, args.json);
+            } else if lib_name.is_empty() {
                 let libs_list = get_library_names_from_toml(&args.libraries, &replacements);
                 if libs_list.is_empty() {
                     println!("No libraries defined in {}", &args.libraries);
@@ -168,17 +459,65 @@ fn main() {
         _ => {
             match args.command.as_str() {
                 "files" => {
-                    write_lib_lists(lib_name, args.toplevel, &args.libraries, &args.tool, &replacements, &args.filename);
+                    write_lib_lists(lib_name, &toplevels, &args.libraries, &args.tool, &replacements, &overrides, &args.filename);
                 }
                 "json" => {
-                    write_json_file(lib_name, args.toplevel, &args.libraries, &args.tool, &replacements, &args.filename);
+                    write_json_file(lib_name, &toplevels, &args.libraries, &args.tool, &replacements, &overrides, &args.filename);
                 }
                 "script" => {
-                    gen_script(lib_name, args.toplevel, &args.libraries, &args.tool, &replacements, &args.filename);
+                    gen_script(lib_name, &toplevels, &args.libraries, &args.tool, &replacements, &args.filename, &options);
                 }
                 "execute" => {
-                    gen_script(lib_name, args.toplevel, &args.libraries, &args.tool, &replacements, &args.filename);
-                    run_script(&args.filename);
+                    exit(execute(lib_name, &toplevels, &args.libraries, &args.tool, &replacements, &options));
+                }
+                "make" => {
+                    write_makefile(lib_name, &toplevels, &args.libraries, &args.tool, &replacements, &args.filename, &options);
+                }
+                "libmap" => {
+                    write_libmap(lib_name, &toplevels, &args.libraries, &args.tool, &replacements, &args.filename, &options);
+                }
+                "vhdl-ls" => {
+                    write_vhdl_ls_toml(lib_name, &toplevels, &args.libraries, &args.tool, &replacements, &overrides, &args.filename);
+                }
+                "tree" => {
+                    let tree_options = TreeOptions {
+                        depth: args.depth,
+                        libraries: args.library_filter.clone(),
+                        json: args.json,
+                    };
+                    print_tree(args.toplevel, &args.libraries, &args.tool, &replacements, &overrides, &tree_options);
+                }
+                "graph" => {
+                    print_graph(args.toplevel, &args.libraries, &args.tool, &replacements, &overrides, &args.kind, &args.format);
+                }
+                "affected" => {
+                    print_affected(&args.libraries, &args.tool, &replacements, &args.changed, &args.git_diff, args.json);
+                }
+                "test" => {
+                    let test_options = TestOptions {
+                        pattern: args.pattern.clone(),
+                        junit: args.junit.clone(),
+                        timeout: args.timeout,
+                        fail_patterns: args.fail_patterns.clone(),
+                        jobs: args.jobs,
+                        generics: generics.clone(),
+                    };
+                    exit(run_tests(&args.libraries, &args.tool, &replacements, &overrides, &test_options));
+                }
+                "unused" => {
+                    print_unused(lib_name, &toplevels, &args.libraries, &args.tool, &replacements, &overrides, args.json);
+                }
+                "check" => {
+                    exit(print_check(&lib_name, &args.libraries, &args.tool, &replacements, args.json));
+                }
+                "gen-config" => {
+                    write_configuration(args.toplevel, &args.libraries, &args.tool, &replacements, &overrides, &args.config_name, &args.filename);
+                }
+                "why" => {
+                    print_why(args.toplevel, &args.libraries, &args.tool, &replacements, &overrides, &args.target);
+                }
+                "ninja" => {
+                    write_ninja_file(lib_name, &toplevels, &args.libraries, &args.tool, &replacements, &args.filename, &options);
                 }
                 _ => {
                     warn!("{} is not implemented yet", args.command)
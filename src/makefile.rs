@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+use std::fs;
+use std::process::exit;
+
+use crate::classes::{ScriptOptions, ToolCall};
+use crate::{
+    expand_toplevels, get_compile_calls, get_element_list, get_file_dependencies, get_sorted_libraries,
+    get_stage_calls, get_stamp_names, read_tool_toml,
+};
+
+// '$' has to be doubled in recipes
+fn recipe(call: &ToolCall) -> String {
+    format!("\t{}", call.to_line().replace('$', "$$"))
+}
+
+pub fn write_makefile(
+    lib_name: String, toplevels: &[String], libraries_toml_filename: &String, tool_toml_filename: &String,
+    replacements: &HashMap<String, String>, filename: &String, options: &ScriptOptions,
+) {
+    let (element_list, libraries) = get_element_list(lib_name.clone(), toplevels, libraries_toml_filename, tool_toml_filename, replacements, &options.overrides);
+    let toplevels = expand_toplevels(&libraries, &lib_name, toplevels);
+    let lib_order = get_sorted_libraries(&libraries);
+    let tool_config = read_tool_toml(tool_toml_filename, replacements);
+    if element_list.is_empty() {
+        eprintln!("No files found to write out!");
+        exit(1);
+    }
+    let stamps = get_stamp_names(&element_list);
+    let file_dependencies = get_file_dependencies(&element_list);
+    let stamp = |filename: &String| format!("$(HANNA_STAMPS)/{}", stamps[filename]);
+
+    let mut content: Vec<String> = Vec::new();
+    content.push(format!("# Makefile for {}, generated by hanna", toplevels.join(", ")));
+    content.push(String::from("HANNA_STAMPS ?= ") + &options.stamps_dir);
+    content.push(String::from(""));
+    content.push(String::from(".PHONY: compile elaborate run clean"));
+    content.push(String::from(""));
+
+    // compile is the default target
+    let mut all_stamps: Vec<String> = Vec::new();
+    for el in &element_list {
+        let s = stamp(&el.filename);
+        if !all_stamps.contains(&s) {
+            all_stamps.push(s);
+        }
+    }
+    content.push(format!("compile: {}", all_stamps.join(" \\\n    ")));
+    for entry in &tool_config.exec_after {
+        content.push(format!("\t{}", entry.replace('$', "$$")));
+    }
+    content.push(String::from(""));
+    content.push(String::from("elaborate: compile"));
+    for toplevel in &toplevels {
+        content.extend(get_stage_calls(&tool_config, toplevel, "elaborate", &[]).iter().map(recipe));
+    }
+    content.push(String::from(""));
+    content.push(String::from("run: elaborate"));
+    for toplevel in &toplevels {
+        content.extend(get_stage_calls(&tool_config, toplevel, "run", &[]).iter().map(recipe));
+    }
+    content.push(String::from(""));
+    content.push(String::from("clean:"));
+    content.push(String::from("\trm -rf $(HANNA_STAMPS)"));
+    content.push(String::from(""));
+
+    content.push(String::from("$(HANNA_STAMPS)/.before:"));
+    content.push(String::from("\t@mkdir -p $(@D)"));
+    for entry in &tool_config.exec_before {
+        content.push(format!("\t{}", entry.replace('$', "$$")));
+    }
+    content.push(String::from("\t@touch $@"));
+    content.push(String::from(""));
+
+    for lib_name in &lib_order {
+        let lib_elements: Vec<_> = element_list.iter().filter(|el| &el.library == lib_name).collect();
+        if lib_elements.is_empty() {
+            continue;
+        }
+        content.push(format!("$(HANNA_STAMPS)/{}/.library: | $(HANNA_STAMPS)/.before", lib_name));
+        content.push(String::from("\t@mkdir -p $(@D)"));
+        for entry in tool_config.exec_per_lib.iter()
+            .chain(tool_config.vhdl.exec_per_lib.iter())
+            .chain(tool_config.verilog.exec_per_lib.iter()) {
+            content.push(format!("\t{}", entry.replace("{library}", lib_name).replace('$', "$$")));
+        }
+        content.push(String::from("\t@touch $@"));
+        content.push(String::from(""));
+
+        // files of one library are compiled one after the other, the order only
+        // prerequisite on the file before keeps 'make -j' from compiling them in parallel
+        let mut done: Vec<&String> = Vec::new();
+        for el in lib_elements {
+            if done.contains(&&el.filename) {
+                continue;
+            }
+            let mut prerequisites: Vec<String> = vec![el.filename.clone()];
+            for dep in file_dependencies.get(&el.filename).unwrap_or(&Vec::new()) {
+                if stamps.contains_key(dep) {
+                    prerequisites.push(stamp(dep));
+                }
+            }
+            let mut order_only = format!("$(HANNA_STAMPS)/{}/.library", lib_name);
+            if let Some(before) = done.last() {
+                order_only.push(' ');
+                order_only.push_str(&stamp(before));
+            }
+            content.push(format!("{}: {} | {}", stamp(&el.filename), prerequisites.join(" "), order_only));
+            for call in get_compile_calls(&tool_config, &el.language, lib_name, std::slice::from_ref(&el.filename)) {
+                content.push(recipe(&call));
+            }
+            content.push(String::from("\t@touch $@"));
+            content.push(String::from(""));
+            done.push(&el.filename);
+        }
+    }
+
+    match fs::write(filename, content.join("\n")) {
+        Ok(_) => println!("{} was written!", filename),
+        Err(err) => {
+            eprintln!("Can't write to '{}'\n{}", filename, err);
+            exit(1);
+        }
+    }
+}
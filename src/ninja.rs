@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::process::exit;
+
+use crate::classes::{ScriptOptions, ToolCall};
+use crate::{
+    expand_toplevels, get_compile_calls, get_element_list, get_file_dependencies, get_sorted_libraries,
+    get_stage_calls, get_stamp_names, read_tool_toml,
+};
+
+const LIBRARY: &str = "@HANNA_LIBRARY@";
+const INPUT: &str = "@HANNA_IN@";
+
+fn escape_path(path: &str) -> String {
+    path.replace('$', "$$").replace(' ', "$ ").replace(':', "$:")
+}
+
+// one command line out of several calls, '$' escaped for ninja
+fn command(calls: &[ToolCall]) -> String {
+    let lines: Vec<String> = calls.iter().map(|c| c.to_line()).collect();
+    lines
+        .join(" && ")
+        .replace('$', "$$")
+        .replace(LIBRARY, "$library")
+        .replace(INPUT, "$in")
+}
+
+fn hooks(entries: &[&String], touch: bool) -> String {
+    let mut ret: Vec<String> = entries
+        .iter()
+        .map(|e| e.replace('$', "$$").replace("{library}", "$library"))
+        .collect();
+    if touch {
+        ret.push(String::from("touch $out"));
+    }
+    if ret.is_empty() {
+        return String::from("true");
+    }
+    ret.join(" && ")
+}
+
+pub fn write_ninja_file(
+    lib_name: String, toplevels: &[String], libraries_toml_filename: &String, tool_toml_filename: &String,
+    replacements: &HashMap<String, String>, filename: &String, options: &ScriptOptions,
+) {
+    let (element_list, libraries) = get_element_list(lib_name.clone(), toplevels, libraries_toml_filename, tool_toml_filename, replacements, &options.overrides);
+    let toplevel = toplevels.join(",");
+    let toplevels = expand_toplevels(&libraries, &lib_name, toplevels);
+    let lib_order = get_sorted_libraries(&libraries);
+    let tool_config = read_tool_toml(tool_toml_filename, replacements);
+    if element_list.is_empty() {
+        eprintln!("No files found to write out!");
+        exit(1);
+    }
+    let stamps = get_stamp_names(&element_list);
+    let file_dependencies = get_file_dependencies(&element_list);
+    let stamp = |filename: &String| format!("$stamps/{}", escape_path(&stamps[filename]));
+
+    let mut content: Vec<String> = Vec::new();
+    content.push(format!("# build.ninja for {}, generated by hanna", toplevels.join(", ")));
+    content.push(String::from("ninja_required_version = 1.3"));
+    content.push(format!("stamps = {}", escape_path(&options.stamps_dir)));
+    content.push(String::from(""));
+
+    // regenerate if the TOML files or one of the analyzed sources changed
+    let hanna = env::current_exe()
+        .ok()
+        .and_then(|p| p.to_str().map(String::from))
+        .unwrap_or(String::from("hanna"));
+    let mut regen: Vec<String> = vec![hanna, String::from("ninja")];
+    let mut replacement_args: Vec<String> = replacements.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+    replacement_args.sort();
+    for replacement in replacement_args {
+        regen.push(String::from("-r"));
+        regen.push(replacement);
+    }
+    regen.extend([
+        String::from("-l"), libraries_toml_filename.clone(),
+        String::from("-t"), tool_toml_filename.clone(),
+        String::from("-n"), filename.clone(),
+        String::from("--stamps"), options.stamps_dir.clone(),
+    ]);
+    // the regenerated file has to resolve the same hierarchy
+    for (path, target) in &options.overrides.binds {
+        regen.push(String::from("--bind"));
+        regen.push(format!("{}={}", path, target));
+    }
+    for (entity, arch) in &options.overrides.archs {
+        regen.push(String::from("--arch"));
+        regen.push(format!("{}={}", entity, arch));
+    }
+    regen.push(toplevel.clone());
+    let regen: Vec<String> = regen.iter().map(|a| format!("'{}'", a.replace('\'', "'\\''"))).collect();
+    content.push(String::from("rule hanna_regen"));
+    content.push(format!("  command = {}", regen.join(" ").replace('$', "$$")));
+    content.push(String::from("  description = Regenerating $out"));
+    content.push(String::from("  depfile = $out.d"));
+    content.push(String::from("  generator = 1"));
+    content.push(String::from(""));
+    content.push(format!(
+        "build {}: hanna_regen {} {}",
+        escape_path(filename),
+        escape_path(libraries_toml_filename),
+        escape_path(tool_toml_filename)
+    ));
+    content.push(String::from(""));
+
+    // the stamp holds the checksum of the source and the stamps it depends on, with restat the
+    // dependents are only compiled again if the content of one of them changed and not only its time stamp
+    let checksum = "sum=$$(cat $in $deps | cksum) && { [ \"$$(cat $out 2>/dev/null)\" = \"$$sum\" ] || echo \"$$sum\" > $out; }";
+    for lang in ["verilog", "vhdl"] {
+        if !element_list.iter().any(|el| el.language == lang) {
+            continue;
+        }
+        let calls = get_compile_calls(&tool_config, lang, LIBRARY, &[String::from(INPUT)]);
+        content.push(format!("rule compile_{}", lang));
+        if calls.is_empty() {
+            content.push(format!("  command = {}", checksum));
+        } else {
+            content.push(format!("  command = {} && {}", command(&calls), checksum));
+        }
+        content.push(format!("  description = Compiling {} $in into $library", lang));
+        content.push(String::from("  restat = 1"));
+        content.push(String::from(""));
+    }
+    let before: Vec<&String> = tool_config.exec_before.iter().collect();
+    content.push(String::from("rule hanna_before"));
+    content.push(format!("  command = {}", hooks(&before, true)));
+    content.push(String::from(""));
+    let per_lib: Vec<&String> = tool_config.exec_per_lib.iter()
+        .chain(tool_config.vhdl.exec_per_lib.iter())
+        .chain(tool_config.verilog.exec_per_lib.iter())
+        .collect();
+    content.push(String::from("rule hanna_library"));
+    content.push(format!("  command = {}", hooks(&per_lib, true)));
+    content.push(String::from("  description = Preparing library $library"));
+    content.push(String::from(""));
+    let after: Vec<&String> = tool_config.exec_after.iter().collect();
+    content.push(String::from("rule hanna_after"));
+    content.push(format!("  command = {}", hooks(&after, false)));
+    content.push(String::from(""));
+    for stage in ["elaborate", "run"] {
+        let calls: Vec<ToolCall> = toplevels.iter().flat_map(|t| get_stage_calls(&tool_config, t, stage, &[])).collect();
+        content.push(format!("rule hanna_{}", stage));
+        if calls.is_empty() {
+            content.push(String::from("  command = true"));
+        } else {
+            content.push(format!("  command = {}", command(&calls)));
+        }
+        content.push(String::from("  pool = console"));
+        content.push(String::from(""));
+    }
+
+    content.push(String::from("build $stamps/.before: hanna_before"));
+    content.push(String::from(""));
+    let mut all_stamps: Vec<String> = Vec::new();
+    for lib_name in &lib_order {
+        let lib_elements: Vec<_> = element_list.iter().filter(|el| &el.library == lib_name).collect();
+        if lib_elements.is_empty() {
+            continue;
+        }
+        content.push(format!("build $stamps/{}/.library: hanna_library || $stamps/.before", lib_name));
+        content.push(format!("  library = {}", lib_name));
+        content.push(String::from(""));
+        // files of one library are compiled one after the other
+        let mut done: Vec<&String> = Vec::new();
+        for el in lib_elements {
+            if done.contains(&&el.filename) {
+                continue;
+            }
+            let mut implicit: Vec<String> = Vec::new();
+            for dep in file_dependencies.get(&el.filename).unwrap_or(&Vec::new()) {
+                if stamps.contains_key(dep) {
+                    implicit.push(stamp(dep));
+                }
+            }
+            let mut order_only = format!("$stamps/{}/.library", lib_name);
+            if let Some(before) = done.last() {
+                order_only.push(' ');
+                order_only.push_str(&stamp(before));
+            }
+            let deps = implicit.join(" ");
+            let implicit = if implicit.is_empty() {
+                String::new()
+            } else {
+                format!(" | {}", deps)
+            };
+            content.push(format!(
+                "build {}: compile_{} {}{} || {}",
+                stamp(&el.filename),
+                el.language,
+                escape_path(&el.filename),
+                implicit,
+                order_only
+            ));
+            content.push(format!("  library = {}", lib_name));
+            if !deps.is_empty() {
+                content.push(format!("  deps = {}", deps));
+            }
+            all_stamps.push(stamp(&el.filename));
+            done.push(&el.filename);
+        }
+        content.push(String::from(""));
+    }
+    content.push(format!("build compile: hanna_after {}", all_stamps.join(" ")));
+    content.push(String::from("build elaborate: hanna_elaborate compile"));
+    content.push(String::from("build run: hanna_run elaborate"));
+    content.push(String::from(""));
+    content.push(String::from("default compile"));
+    content.push(String::from(""));
+
+    match fs::write(filename, content.join("\n")) {
+        Ok(_) => println!("{} was written!", filename),
+        Err(err) => {
+            eprintln!("Can't write to '{}'\n{}", filename, err);
+            exit(1);
+        }
+    }
+    // sources of the analysis, a change of one of them may change the dependencies
+    let mut sources: Vec<String> = Vec::new();
+    for lib in libraries.values() {
+        for el in lib.all_vhdl_elements.iter().chain(lib.all_verilog_elements.iter()) {
+            sources.push(el.filename.replace(' ', "\\ "));
+        }
+    }
+    sources.sort();
+    sources.dedup();
+    let depfile = format!("{}.d", filename);
+    if let Err(err) = fs::write(&depfile, format!("{}: {}\n", filename, sources.join(" \\\n  "))) {
+        eprintln!("Can't write to '{}'\n{}", depfile, err);
+        exit(1);
+    }
+}
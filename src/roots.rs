@@ -0,0 +1,102 @@
+use std::collections::{HashMap, HashSet};
+use std::process::exit;
+
+use log::error;
+
+use crate::classes::{Library, RootUnit};
+use crate::unused::base_unit;
+use crate::{get_all_elements, get_libraries};
+
+pub fn is_testbench(lib: &Library, entity: &str) -> bool {
+    let name = entity.to_lowercase();
+    name.starts_with("tb_") || name.ends_with("_tb") || lib.designs.get(entity).is_some_and(|d| !d.entity.has_ports)
+}
+
+// (library, unit) of all instantiated units, entities of instantiated configurations included
+fn get_instantiated(libraries: &HashMap<String, Library>) -> HashSet<(String, String)> {
+    let mut ret: HashSet<(String, String)> = HashSet::new();
+    for el in get_all_elements(libraries) {
+        for dep in el.depends_on.iter().filter(|d| d.kind == "instance") {
+            // recursive instantiation doesn't make a unit less of a root
+            if dep.library == el.library && base_unit(&dep.unit) == base_unit(&el.unit) {
+                continue;
+            }
+            ret.insert((dep.library.clone(), base_unit(&dep.unit).to_string()));
+            if let Some(conf) = libraries.get(&dep.library).and_then(|l| l.configurations.get(&dep.unit)) {
+                ret.insert((dep.library.clone(), conf.entity.clone()));
+            }
+        }
+    }
+    ret
+}
+
+// architectures and configurations no other unit instantiates or configures
+pub fn get_roots(libraries: &HashMap<String, Library>, lib_name: &str) -> Vec<RootUnit> {
+    let instantiated = get_instantiated(libraries);
+    let mut names: Vec<&String> = libraries
+        .iter()
+        .filter(|(name, lib)| (lib_name.is_empty() || *name == lib_name) && lib.is_compiled())
+        .map(|(name, _)| name)
+        .collect();
+    names.sort();
+    let mut ret: Vec<RootUnit> = Vec::new();
+    for name in names {
+        let lib = &libraries[name];
+        let is_root = |entity: &str| !instantiated.contains(&(name.clone(), entity.to_string()));
+        let mut configurations: Vec<&String> = lib.configurations.keys().collect();
+        configurations.sort();
+        let configurations: Vec<RootUnit> = configurations
+            .into_iter()
+            .filter(|c| is_root(c))
+            .map(|c| {
+                let entity = &lib.configurations[c].entity;
+                let mut root = RootUnit::new(name, c, "configuration", entity);
+                root.testbench = is_testbench(lib, entity);
+                // the configuration of an instantiated entity isn't meant as toplevel
+                root.preferred = is_root(entity);
+                root
+            })
+            .collect();
+        for design in lib.get_design_names().iter().filter(|d| is_root(d)) {
+            let mut architectures: Vec<&String> = lib.designs[design].architectures.keys().collect();
+            architectures.sort();
+            let configured = configurations.iter().any(|c| &c.entity == design);
+            for arch in architectures {
+                let mut root = RootUnit::new(name, &format!("{}({})", design, arch), "architecture", design);
+                root.testbench = is_testbench(lib, design);
+                // a configuration of the entity is the better toplevel
+                root.preferred = !configured;
+                ret.push(root);
+            }
+        }
+        ret.extend(configurations);
+    }
+    ret
+}
+
+pub fn print_roots(
+    lib_name: &String, libraries_toml_filename: &String, tool_toml_filename: &String,
+    replacements: &HashMap<String, String>, json: bool,
+) {
+    let libraries = get_libraries(libraries_toml_filename, tool_toml_filename, replacements);
+    if !lib_name.is_empty() && !libraries.contains_key(lib_name) {
+        error!("A lib with name {} is not defined!", lib_name);
+        exit(1)
+    }
+    let roots = get_roots(&libraries, lib_name);
+    if json {
+        let content: Vec<serde_json::Value> = roots.iter().map(|r| r.to_json()).collect();
+        println!("{}", serde_json::to_string_pretty(&content).unwrap_or_default());
+        return;
+    }
+    if roots.is_empty() {
+        println!("No roots found");
+        return;
+    }
+    println!("Roots (* preferred):");
+    for root in &roots {
+        let testbench = if root.testbench { ", testbench" } else { "" };
+        let preferred = if root.preferred { "*" } else { " " };
+        println!(" {} {}.{} ({}{})", preferred, root.library, root.unit, root.kind, testbench);
+    }
+}
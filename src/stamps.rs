@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use log::{debug, warn};
+use serde_json::{json, Value};
+
+use crate::classes::BindingOverrides;
+
+// FNV-1a, stable between runs and Rust versions
+pub fn hash_content(content: &[u8]) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in content {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+// empty if the file can't be read, so it always counts as changed
+pub fn hash_file(filename: &str) -> String {
+    match fs::read(filename) {
+        Ok(content) => hash_content(&content),
+        Err(err) => {
+            warn!("Can't read {} for hashing: {}", filename, err);
+            String::new()
+        }
+    }
+}
+
+// hash of everything besides the sources which changes the compilation, a different one
+// invalidates all stamps
+pub fn get_tool_hash(
+    tool_toml_filename: &str, replacements: &HashMap<String, String>, generics: &[(String, String)],
+    overrides: &BindingOverrides,
+) -> String {
+    let mut inputs: Vec<String> = vec![hash_file(tool_toml_filename)];
+    let mut replacements: Vec<String> = replacements.iter().map(|(k, v)| format!("-r {}={}", k, v)).collect();
+    replacements.sort();
+    inputs.extend(replacements);
+    inputs.extend(generics.iter().map(|(name, value)| format!("-g {}={}", name, value)));
+    inputs.extend(overrides.binds.iter().map(|(path, target)| format!("--bind {}={}", path, target)));
+    inputs.extend(overrides.archs.iter().map(|(entity, arch)| format!("--arch {}={}", entity, arch)));
+    hash_content(inputs.join("\n").as_bytes())
+}
+
+// one stamp database per toplevel and tool profile
+pub fn get_stamps_filename(stamps_dir: &str, toplevel: &str, tool_toml_filename: &str) -> String {
+    let profile = Path::new(tool_toml_filename)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("tool");
+    let name: String = format!("{}.{}", toplevel, profile)
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '_' { c } else { '_' })
+        .collect();
+    format!("{}/{}.json", stamps_dir.strip_suffix('/').unwrap_or(stamps_dir), name)
+}
+
+// returns the hash of the tool profile and the hashes of the compiled files
+pub fn read_stamps(filename: &str) -> (String, HashMap<String, String>) {
+    let mut stamps: HashMap<String, String> = HashMap::new();
+    let Ok(content) = fs::read_to_string(filename) else {
+        debug!("No stamps found in {}", filename);
+        return (String::new(), stamps);
+    };
+    let value: Value = match serde_json::from_str(&content) {
+        Ok(value) => value,
+        Err(err) => {
+            warn!("Ignoring broken stamps file {}: {}", filename, err);
+            return (String::new(), stamps);
+        }
+    };
+    let tool = value["tool"].as_str().unwrap_or("").to_string();
+    if let Some(files) = value["files"].as_object() {
+        for (file, hash) in files {
+            stamps.insert(file.clone(), hash.as_str().unwrap_or("").to_string());
+        }
+    }
+    (tool, stamps)
+}
+
+pub fn write_stamps(filename: &str, tool_hash: &str, stamps: &HashMap<String, String>) {
+    if let Some(dir) = Path::new(filename).parent() {
+        if let Err(err) = fs::create_dir_all(dir) {
+            warn!("Can't create directory {:?}: {}", dir, err);
+            return;
+        }
+    }
+    let content = json!({ "tool": tool_hash, "files": stamps });
+    let content = serde_json::to_string_pretty(&content).unwrap_or(String::from(""));
+    if let Err(err) = fs::write(filename, content) {
+        warn!("Can't write stamps to {}: {}", filename, err);
+    }
+}
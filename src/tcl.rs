@@ -0,0 +1,59 @@
+use crate::classes::LibraryFiles;
+use crate::libmap::get_library_dir;
+
+fn tcl_files(files: &[String]) -> String {
+    let files: Vec<String> = files.iter().map(|f| format!("{{{}}}", f)).collect();
+    files.join(" \\\n    ")
+}
+
+// .do file for Questa/ModelSim
+pub fn render_questa_script(units: &[LibraryFiles], vhdl_std: &str, lib_dir: &str) -> String {
+    let mut content: Vec<String> = Vec::new();
+    content.push(String::from("onerror {quit -f -code 1}"));
+    content.push(String::from(""));
+    let mut libraries: Vec<&String> = Vec::new();
+    for unit in units {
+        if !libraries.contains(&&unit.library) {
+            libraries.push(&unit.library);
+        }
+    }
+    for lib in libraries {
+        let dir = get_library_dir(lib_dir, lib);
+        content.push(format!("vlib {}", dir));
+        content.push(format!("vmap {} {}", lib, dir));
+    }
+    for unit in units {
+        content.push(String::from(""));
+        if unit.language == "vhdl" {
+            content.push(format!("vcom -{} -work {} \\\n    {}", vhdl_std, unit.library, tcl_files(&unit.files)));
+        } else {
+            content.push(format!("vlog -work {} \\\n    {}", unit.library, tcl_files(&unit.files)));
+        }
+    }
+    content.push(String::from(""));
+    content.join("\n")
+}
+
+// Tcl script for Vivado
+pub fn render_vivado_script(units: &[LibraryFiles], vhdl_std: &str) -> String {
+    let std_flag = match vhdl_std {
+        "2008" => " -vhdl2008",
+        "2019" => " -vhdl2019",
+        _ => "",
+    };
+    let mut content: Vec<String> = Vec::new();
+    for unit in units {
+        if unit.language == "vhdl" {
+            content.push(format!("read_vhdl -library {}{} [list \\\n    {}]", unit.library, std_flag, tcl_files(&unit.files)));
+        } else {
+            content.push(format!("add_files -norecurse [list \\\n    {}]", tcl_files(&unit.files)));
+            content.push(format!(
+                "set_property library {} [get_files [list \\\n    {}]]",
+                unit.library,
+                tcl_files(&unit.files)
+            ));
+        }
+        content.push(String::from(""));
+    }
+    content.join("\n")
+}
@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use log::error;
+use regex::Regex;
+
+use crate::classes::tool_config::ToolConfig;
+use crate::classes::{BindingOverrides, Library, StepResult, TestOptions, TestResult, ToolTestConfig};
+use crate::execute::{print_summary, run_tool_call, run_tool_calls};
+use crate::roots::get_roots;
+use crate::{
+    get_element_list, get_libraries, get_library_dependencies, get_sorted_libraries, get_stage_calls, get_tool_calls,
+    merge_generics, read_tool_toml, replace_top,
+};
+
+// pattern matches lib.unit, or unit if it has no library
+fn matches(pattern: &str, name: &str) -> bool {
+    let Ok(glob) = glob::Pattern::new(pattern) else {
+        return false;
+    };
+    let unit = name.split_once('.').map_or(name, |(_, u)| u);
+    glob.matches(name) || (!pattern.contains('.') && glob.matches(unit))
+}
+
+// preferred testbench roots of all libraries, pattern matches lib.unit or unit
+pub fn find_testbenches(libraries: &HashMap<String, Library>, pattern: &str) -> Vec<String> {
+    if glob::Pattern::new(pattern).is_err() {
+        error!("No valid pattern: {}", pattern);
+        return Vec::new();
+    }
+    get_roots(libraries, "")
+        .into_iter()
+        .filter(|r| r.testbench && r.preferred)
+        .map(|r| format!("{}.{}", r.library, r.unit))
+        .filter(|name| matches(pattern, name))
+        .collect()
+}
+
+// generics of each run of a testbench, one run without generics if none are configured
+pub fn get_parameterisations(config: &ToolTestConfig, toplevel: &str) -> Vec<Vec<(String, String)>> {
+    let ret: Vec<Vec<(String, String)>> = config
+        .generics
+        .iter()
+        .filter(|(pattern, _)| matches(pattern, toplevel))
+        .map(|(_, generics)| generics.clone())
+        .collect();
+    if ret.is_empty() {
+        vec![Vec::new()]
+    } else {
+        ret
+    }
+}
+
+// reason why the output of a test makes it fail, empty if it passed
+pub fn check_output(output: &[String], config: &ToolTestConfig) -> String {
+    for pattern in &config.fail_patterns {
+        let Ok(re) = Regex::new(pattern) else {
+            continue;
+        };
+        if let Some(line) = output.iter().find(|l| re.is_match(l)) {
+            return format!("output matches '{}': {}", pattern, line.trim());
+        }
+    }
+    if config.pass_patterns.is_empty() {
+        return String::new();
+    }
+    let passed = config
+        .pass_patterns
+        .iter()
+        .filter_map(|p| Regex::new(p).ok())
+        .any(|re| output.iter().any(|l| re.is_match(l)));
+    if passed {
+        String::new()
+    } else {
+        format!("output matches none of '{}'", config.pass_patterns.join("', '"))
+    }
+}
+
+// elaborates and runs one toplevel with its generics in its run directory
+pub fn run_test(tool_config: &ToolConfig, toplevel: &str, generics: &[(String, String)]) -> TestResult {
+    let test_config = &tool_config.test;
+    let mut result = TestResult::new(toplevel);
+    result.generics = generics.to_vec();
+    let mut run_dir = replace_top(&test_config.run_dir, toplevel);
+    if !generics.is_empty() {
+        // each parameterisation gets its own directory
+        let name: Vec<String> = generics
+            .iter()
+            .map(|(n, v)| format!("{}_{}", n, v).replace(|c: char| !c.is_ascii_alphanumeric() && c != '_' && c != '-', "_"))
+            .collect();
+        run_dir = format!("{}/{}", run_dir, name.join("-"));
+    }
+    if let Err(err) = fs::create_dir_all(&run_dir) {
+        result.status = String::from("failed");
+        result.message = format!("Can't create {}: {}", run_dir, err);
+        return result;
+    }
+    let timeout = Duration::from_secs(test_config.timeout);
+    let start = Instant::now();
+    let mut calls = get_stage_calls(tool_config, toplevel, "elaborate", generics);
+    calls.extend(get_stage_calls(tool_config, toplevel, "run", generics));
+    // the compile ran in the current directory, {work_dir} lets the stages of a test refer to it
+    let work_dir = env::current_dir().map(|d| d.to_string_lossy().to_string()).unwrap_or_default();
+    for mut call in calls {
+        for fragment in call.fragments.iter_mut() {
+            *fragment = fragment.replace("{work_dir}", &work_dir);
+        }
+        // a cwd configured for the stage is kept
+        if call.cwd.is_empty() {
+            call.cwd = run_dir.clone();
+        }
+        if !timeout.is_zero() {
+            call.timeout = timeout.saturating_sub(start.elapsed()).max(Duration::from_secs(1));
+        }
+        let step: StepResult = run_tool_call(&call);
+        result.output.extend(step.output);
+        if step.timed_out {
+            result.message = format!("timeout after {}s", test_config.timeout);
+        } else if step.status == "failed" {
+            result.message = format!("{} failed with exit code {}", call.stage, step.exit_code);
+        }
+        if !result.message.is_empty() {
+            break;
+        }
+    }
+    result.duration = start.elapsed();
+    if result.message.is_empty() {
+        result.message = check_output(&result.output, test_config);
+    }
+    result.status = if result.message.is_empty() { "passed" } else { "failed" }.to_string();
+    result
+}
+
+fn escape_xml(text: &str) -> String {
+    text.chars()
+        .filter(|c| !c.is_control() || *c == '\n' || *c == '\t')
+        .collect::<String>()
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+pub fn render_junit(compile_results: &[StepResult], results: &[TestResult]) -> String {
+    let mut cases: Vec<String> = Vec::new();
+    let compile_failed = compile_results.iter().find(|r| r.status == "failed");
+    if let Some(failed) = compile_failed {
+        let output = escape_xml(&failed.output.join("\n"));
+        cases.push(format!(
+            "    <testcase classname=\"hanna\" name=\"compile\" time=\"{:.3}\">\n      <failure message=\"compile of {} failed with exit code {}\">{}</failure>\n    </testcase>",
+            compile_results.iter().map(|r| r.duration.as_secs_f64()).sum::<f64>(),
+            escape_xml(&failed.library), failed.exit_code, output
+        ));
+    }
+    for result in results {
+        let name = result.name();
+        let (library, unit) = name.split_once('.').unwrap_or(("", &name));
+        let mut case = format!(
+            "    <testcase classname=\"{}\" name=\"{}\" time=\"{:.3}\"",
+            escape_xml(library), escape_xml(unit), result.duration.as_secs_f64()
+        );
+        match result.status.as_str() {
+            "passed" => case.push_str(">\n"),
+            "skipped" => case.push_str(&format!(">\n      <skipped message=\"{}\"/>\n", escape_xml(&result.message))),
+            _ => case.push_str(&format!(">\n      <failure message=\"{}\"/>\n", escape_xml(&result.message))),
+        }
+        if !result.output.is_empty() {
+            case.push_str(&format!("      <system-out>{}</system-out>\n", escape_xml(&result.output.join("\n"))));
+        }
+        case.push_str("    </testcase>");
+        cases.push(case);
+    }
+    let failures = results.iter().filter(|r| r.status == "failed").count() + usize::from(compile_failed.is_some());
+    let skipped = results.iter().filter(|r| r.status == "skipped").count();
+    let time: f64 = compile_results.iter().map(|r| r.duration.as_secs_f64()).sum::<f64>()
+        + results.iter().map(|r| r.duration.as_secs_f64()).sum::<f64>();
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites>\n  <testsuite name=\"hanna\" tests=\"{}\" failures=\"{}\" errors=\"0\" skipped=\"{}\" time=\"{:.3}\">\n{}\n  </testsuite>\n</testsuites>\n",
+        cases.len(), failures, skipped, time, cases.join("\n")
+    )
+}
+
+pub fn print_test_summary(results: &[TestResult]) {
+    let width = results.iter().map(|r| r.name().len()).max().unwrap_or(0).max("Test".len());
+    println!();
+    println!("{:<width$} {:<8} {:>9}  Details", "Test", "Status", "Time");
+    for result in results {
+        let time = if result.status == "skipped" {
+            String::new()
+        } else {
+            format!("{:.2}s", result.duration.as_secs_f64())
+        };
+        let line = format!("{:<width$} {:<8} {:>9}  {}", result.name(), result.status, time, result.message);
+        println!("{}", line.trim_end());
+    }
+    let passed = results.iter().filter(|r| r.status == "passed").count();
+    println!("{} of {} test(s) passed", passed, results.len());
+}
+
+// compiles the files of all testbenches once, then elaborates and runs each of them
+pub fn run_tests(
+    libraries_toml_filename: &String, tool_toml_filename: &String, replacements: &HashMap<String, String>,
+    overrides: &BindingOverrides, options: &TestOptions,
+) -> i32 {
+    let libraries = get_libraries(libraries_toml_filename, tool_toml_filename, replacements);
+    let testbenches = find_testbenches(&libraries, &options.pattern);
+    if testbenches.is_empty() {
+        eprintln!("No testbenches match {}", options.pattern);
+        return 1;
+    }
+    println!("Testbenches:\n - {}", testbenches.join("\n - "));
+    let lib_name = testbenches[0].split_once('.').map_or("", |(l, _)| l).to_string();
+    let (element_list, libraries) = get_element_list(
+        lib_name, &testbenches, libraries_toml_filename, tool_toml_filename, replacements, overrides,
+    );
+    let mut tool_config = read_tool_toml(tool_toml_filename, replacements);
+    tool_config.test.fail_patterns.extend(options.fail_patterns.iter().cloned());
+    if options.timeout > 0 {
+        tool_config.test.timeout = options.timeout;
+    }
+
+    let calls = get_tool_calls(&element_list, &get_sorted_libraries(&libraries), &tool_config, &[], &String::from("compile"), &[]);
+    let compile_results = run_tool_calls(&calls, &get_library_dependencies(&libraries), options.jobs);
+    print_summary(&compile_results);
+    let compiled = compile_results.iter().all(|r| r.status != "failed");
+    let mut results: Vec<TestResult> = Vec::new();
+    for toplevel in &testbenches {
+        for parameterisation in get_parameterisations(&tool_config.test, toplevel) {
+            let generics = merge_generics(&parameterisation, &options.generics);
+            if compiled {
+                results.push(run_test(&tool_config, toplevel, &generics));
+            } else {
+                let mut result = TestResult::new(toplevel);
+                result.generics = generics;
+                result.message = String::from("compilation failed");
+                results.push(result);
+            }
+        }
+    }
+    print_test_summary(&results);
+
+    let junit = render_junit(&compile_results, &results);
+    if let Some(parent) = Path::new(&options.junit).parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    match fs::write(&options.junit, junit) {
+        Ok(_) => println!("{} was written!", options.junit),
+        Err(err) => error!("Can't write {}: {}", options.junit, err),
+    }
+    if compiled && results.iter().all(|r| r.status == "passed") {
+        0
+    } else {
+        1
+    }
+}
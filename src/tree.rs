@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+use std::process::exit;
+
+use crate::classes::{BindingOverrides, HierarchyNode, TreeOptions};
+use crate::{get_hierarchy, get_single_element_list};
+
+// instance hierarchy, configurations and architectures in between are left out
+pub fn get_instance_tree(hierarchy: &HierarchyNode) -> HierarchyNode {
+    let mut node = hierarchy.copy_node();
+    node.children = instance_children(hierarchy);
+    node
+}
+
+fn instance_children(node: &HierarchyNode) -> Vec<HierarchyNode> {
+    let mut ret: Vec<HierarchyNode> = Vec::new();
+    for child in &node.children {
+        match child.kind.as_str() {
+            "instance" => {
+                let mut instance = child.copy_node();
+                instance.children = instance_children(child);
+                ret.push(instance);
+            }
+            "architecture" => ret.extend(instance_children(child)),
+            _ => {}
+        }
+    }
+    ret
+}
+
+// cut the tree below depth and keep only the branches leading to one of the libraries
+pub fn filter_tree(node: &HierarchyNode, options: &TreeOptions, level: usize) -> Option<HierarchyNode> {
+    let mut ret = node.copy_node();
+    if options.depth == 0 || level < options.depth {
+        ret.children = node
+            .children
+            .iter()
+            .filter_map(|child| filter_tree(child, options, level + 1))
+            .collect();
+    }
+    if level > 0
+        && !options.libraries.is_empty()
+        && !options.libraries.contains(&ret.library)
+        && ret.children.is_empty()
+    {
+        return None;
+    }
+    Some(ret)
+}
+
+fn node_text(node: &HierarchyNode) -> String {
+    let mut text = format!("{}.{}", node.library, node.unit);
+    if !node.language.is_empty() {
+        text.push_str(&format!(" [{}]", node.language));
+    }
+    if !node.label.is_empty() {
+        text = format!("{}: {}", node.label, text);
+    }
+    if !node.binding.is_empty() {
+        text.push_str(&format!(" ({})", node.binding));
+    }
+    text
+}
+
+pub fn tree_lines(node: &HierarchyNode) -> Vec<String> {
+    let mut lines: Vec<String> = vec![node_text(node)];
+    add_tree_lines(node, "", &mut lines);
+    lines
+}
+
+fn add_tree_lines(node: &HierarchyNode, prefix: &str, lines: &mut Vec<String>) {
+    for (i, child) in node.children.iter().enumerate() {
+        let last = i + 1 == node.children.len();
+        let (branch, indent) = if last { ("└── ", "    ") } else { ("├── ", "│   ") };
+        lines.push(format!("{}{}{}", prefix, branch, node_text(child)));
+        add_tree_lines(child, &format!("{}{}", prefix, indent), lines);
+    }
+}
+
+pub fn print_tree(
+    toplevel: String, libraries_toml_filename: &String, tool_toml_filename: &String,
+    replacements: &HashMap<String, String>, overrides: &BindingOverrides, options: &TreeOptions,
+) {
+    let (element_list, _) = get_single_element_list(&toplevel, libraries_toml_filename, tool_toml_filename, replacements, overrides);
+    if element_list.is_empty() {
+        eprintln!("No files found!");
+        exit(1);
+    }
+    let tree = get_instance_tree(&get_hierarchy(&element_list));
+    let Some(tree) = filter_tree(&tree, options, 0) else {
+        return;
+    };
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&tree.to_json()).unwrap_or_default());
+    } else {
+        println!("{}", tree_lines(&tree).join("\n"));
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::classes::{BindingOverrides, Element, Library};
+use crate::{get_all_elements, get_element_list};
+
+// entity of an architecture or package of a package body, the unit itself otherwise
+pub(crate) fn base_unit(unit: &str) -> &str {
+    match unit.split_once('(') {
+        Some((entity, _)) => entity,
+        None => unit.strip_suffix(" body").unwrap_or(unit),
+    }
+}
+
+fn sorted_names(libraries: &HashMap<String, Library>) -> Vec<&String> {
+    let mut names: Vec<&String> = libraries
+        .iter()
+        .filter(|(_, lib)| lib.is_compiled())
+        .map(|(name, _)| name)
+        .collect();
+    names.sort();
+    names
+}
+
+// files found by the globs of a library, which none of the toplevels compiles into it
+pub fn get_unused_files(libraries: &HashMap<String, Library>, element_list: &[Element]) -> Vec<(String, String)> {
+    let used: HashSet<(&str, &str)> = element_list
+        .iter()
+        .map(|el| (el.library.as_str(), el.filename.as_str()))
+        .collect();
+    let mut ret: Vec<(String, String)> = Vec::new();
+    for lib_name in sorted_names(libraries) {
+        let lib = &libraries[lib_name];
+        for el in lib.all_vhdl_elements.iter().chain(lib.all_verilog_elements.iter()) {
+            if !used.contains(&(lib_name.as_str(), el.filename.as_str())) {
+                ret.push((lib_name.clone(), el.filename.clone()));
+            }
+        }
+    }
+    ret
+}
+
+// units no other unit references, (library, unit, kind), these are toplevels or dead code
+pub fn get_unreferenced_units(libraries: &HashMap<String, Library>) -> Vec<(String, String, String)> {
+    let mut referenced: HashSet<(String, String)> = HashSet::new();
+    for el in get_all_elements(libraries) {
+        for dep in &el.depends_on {
+            // an architecture doesn't reference its own entity
+            if dep.library == el.library && base_unit(&dep.unit) == base_unit(&el.unit) {
+                continue;
+            }
+            referenced.insert((dep.library.clone(), dep.unit.clone()));
+            referenced.insert((dep.library.clone(), base_unit(&dep.unit).to_string()));
+        }
+    }
+    let mut ret: Vec<(String, String, String)> = Vec::new();
+    for lib_name in sorted_names(libraries) {
+        let lib = &libraries[lib_name];
+        let mut units: Vec<(String, &str)> = Vec::new();
+        let mut packages: Vec<&String> = lib.packages.keys().collect();
+        packages.sort();
+        units.extend(packages.into_iter().map(|p| (p.clone(), "package")));
+        for design in lib.get_design_names() {
+            units.push((design.clone(), "entity"));
+            let mut architectures: Vec<&String> = lib.designs[&design].architectures.keys().collect();
+            architectures.sort();
+            units.extend(architectures.into_iter().map(|a| (format!("{}({})", design, a), "architecture")));
+        }
+        let mut configurations: Vec<&String> = lib.configurations.keys().collect();
+        configurations.sort();
+        units.extend(configurations.into_iter().map(|c| (c.clone(), "configuration")));
+        let mut modules: Vec<&String> = lib.modules.keys().filter(|m| !lib.designs.contains_key(*m)).collect();
+        modules.sort();
+        units.extend(modules.into_iter().map(|m| (m.clone(), "module")));
+        for (unit, kind) in units {
+            if !referenced.contains(&(lib_name.clone(), unit.clone())) {
+                ret.push((lib_name.clone(), unit, kind.to_string()));
+            }
+        }
+    }
+    ret
+}
+
+pub fn print_unused(
+    lib_name: String, toplevels: &[String], libraries_toml_filename: &String, tool_toml_filename: &String,
+    replacements: &HashMap<String, String>, overrides: &BindingOverrides, json: bool,
+) {
+    let (element_list, libraries) = get_element_list(lib_name, toplevels, libraries_toml_filename, tool_toml_filename, replacements, overrides);
+    let files = get_unused_files(&libraries, &element_list);
+    let units = get_unreferenced_units(&libraries);
+    if json {
+        let files: Vec<serde_json::Value> = files
+            .iter()
+            .map(|(library, filename)| serde_json::json!({"library": library, "filename": filename}))
+            .collect();
+        let units: Vec<serde_json::Value> = units
+            .iter()
+            .map(|(library, unit, kind)| serde_json::json!({"library": library, "unit": unit, "kind": kind}))
+            .collect();
+        let content = serde_json::json!({"files": files, "units": units});
+        println!("{}", serde_json::to_string_pretty(&content).unwrap_or_default());
+        return;
+    }
+    if files.is_empty() {
+        println!("All files are used.");
+    } else {
+        println!("Files no toplevel needs:");
+        for (library, filename) in &files {
+            println!(" - {}: {}", library, filename);
+        }
+    }
+    if !units.is_empty() {
+        println!("Units no other unit references:");
+        for (library, unit, kind) in &units {
+            println!(" - {}.{} ({})", library, unit, kind);
+        }
+    }
+}
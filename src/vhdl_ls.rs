@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::exit;
+
+use toml::{Table, Value};
+
+use crate::classes::BindingOverrides;
+use crate::{get_element_list, read_libraries_toml, read_tool_toml};
+
+// vhdl_ls resolves relative paths relative to vhdl_ls.toml, hanna relative to the working directory
+fn absolute(path: &str) -> String {
+    if Path::new(path).is_absolute() {
+        return path.to_string();
+    }
+    match env::current_dir() {
+        Ok(dir) => dir.join(path).to_string_lossy().to_string(),
+        Err(_) => path.to_string(),
+    }
+}
+
+// without a toplevel all libraries with their glob patterns, otherwise only the files the toplevel needs
+pub fn write_vhdl_ls_toml(
+    lib_name: String, toplevels: &[String], libraries_toml_filename: &String, tool_toml_filename: &String,
+    replacements: &HashMap<String, String>, overrides: &BindingOverrides, filename: &String,
+) {
+    let mut files: HashMap<String, Vec<String>> = HashMap::new();
+    let mut third_party: Vec<String> = Vec::new();
+    let whole_project = toplevels.is_empty();
+    let libraries = if whole_project {
+        let tool_config = read_tool_toml(tool_toml_filename, replacements);
+        read_libraries_toml(libraries_toml_filename, replacements, &tool_config)
+    } else {
+        let (element_list, libraries) = get_element_list(lib_name, toplevels, libraries_toml_filename, tool_toml_filename, replacements, overrides);
+        if element_list.is_empty() {
+            eprintln!("No files found to write out!");
+            exit(1);
+        }
+        for el in element_list.iter().filter(|el| el.language == "vhdl") {
+            let lib_files = files.entry(el.library.clone()).or_default();
+            let filename = absolute(&el.filename);
+            if !lib_files.contains(&filename) {
+                lib_files.push(filename);
+            }
+        }
+        libraries
+    };
+    for (name, lib) in &libraries {
+        if lib.ignore {
+            continue;
+        }
+        // sources of precompiled or vendor libraries are only needed to know their units
+        if lib.is_external() {
+            third_party.push(name.clone());
+        } else if !whole_project {
+            continue;
+        }
+        let patterns: Vec<String> = lib.vhdl_scope.iter().map(|p| absolute(p)).collect();
+        if !patterns.is_empty() {
+            files.insert(name.clone(), patterns);
+        }
+    }
+
+    let mut libs = Table::new();
+    let mut names: Vec<&String> = files.keys().collect();
+    names.sort();
+    for name in names {
+        let mut lib = Table::new();
+        lib.insert(
+            String::from("files"),
+            Value::Array(files[name].iter().map(|f| Value::String(f.clone())).collect()),
+        );
+        if third_party.contains(name) {
+            lib.insert(String::from("is_third_party"), Value::Boolean(true));
+        }
+        libs.insert(name.clone(), Value::Table(lib));
+    }
+    let mut content = Table::new();
+    content.insert(String::from("libraries"), Value::Table(libs));
+    match fs::write(filename, content.to_string()) {
+        Ok(_) => println!("{} was written!", filename),
+        Err(err) => {
+            eprintln!("Can't write to '{}'\n{}", filename, err);
+            exit(1);
+        }
+    }
+}
+
@@ -0,0 +1,141 @@
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::Path;
+use std::process::exit;
+
+use regex::Regex;
+
+use crate::classes::{BindingOverrides, Dependency, Element};
+use crate::get_single_element_list;
+
+// the target is a file if it has a path or a HDL extension, otherwise a unit or library.unit
+fn is_target(library: &str, unit: &str, filename: &str, target: &str) -> bool {
+    let extension = Path::new(target).extension().and_then(|e| e.to_str()).unwrap_or("");
+    if target.contains('/') || ["vhd", "vhdl", "v", "sv"].contains(&extension) {
+        return filename == target || filename.ends_with(&format!("/{}", target));
+    }
+    let target = target.to_ascii_lowercase();
+    unit == target || format!("{}.{}", library, unit) == target
+}
+
+// shortest chain of dependencies from the toplevel, the last element of the list, to the target
+pub fn find_chain(element_list: &[Element], target: &str) -> Option<Vec<Dependency>> {
+    let mut elements: HashMap<(&str, &str), &Element> = HashMap::new();
+    for el in element_list {
+        elements.entry((el.library.as_str(), el.unit.as_str())).or_insert(el);
+    }
+    let top = element_list.last()?;
+    if is_target(&top.library, &top.unit, &top.filename, target) {
+        return Some(Vec::new());
+    }
+    let mut visited: Vec<(String, String)> = vec![(top.library.clone(), top.unit.clone())];
+    let mut queue: VecDeque<(&Element, Vec<Dependency>)> = VecDeque::from([(top, Vec::new())]);
+    while let Some((el, chain)) = queue.pop_front() {
+        for dep in &el.depends_on {
+            let key = (dep.library.clone(), dep.unit.clone());
+            if visited.contains(&key) {
+                continue;
+            }
+            visited.push(key);
+            let mut dep_chain: Vec<Dependency> = chain.iter().map(|d| d.copy()).collect();
+            dep_chain.push(dep.copy());
+            if is_target(&dep.library, &dep.unit, &dep.filename, target) {
+                return Some(dep_chain);
+            }
+            if let Some(dep_el) = elements.get(&(dep.library.as_str(), dep.unit.as_str())) {
+                queue.push_back((dep_el, dep_chain));
+            }
+        }
+    }
+    None
+}
+
+fn unit_pattern(unit: &str) -> String {
+    if let Some((entity, arch)) = unit.strip_suffix(')').and_then(|u| u.split_once('(')) {
+        format!(r"\barchitecture\s+{}\s+of\s+{}\b", regex::escape(arch), regex::escape(entity))
+    } else if let Some(package) = unit.strip_suffix(" body") {
+        format!(r"\bpackage\s+body\s+{}\b", regex::escape(package))
+    } else {
+        format!(r"\b(entity|configuration|package|module)\s+{}\b", regex::escape(unit))
+    }
+}
+
+// where the unit in parent_unit refers to the dependency
+fn dependency_pattern(parent_unit: &str, dep: &Dependency) -> String {
+    match dep.kind.as_str() {
+        "instance" => format!(r"^\s*{}\s*:", regex::escape(&dep.label)),
+        "use" => format!(r"\buse\s+{}", regex::escape(&dep.label)),
+        "architecture" => {
+            let arch = dep.unit.split_once('(').map_or("", |(_, a)| a.trim_end_matches(')'));
+            format!(r"\bfor\s+{}\b", regex::escape(arch))
+        }
+        _ => unit_pattern(parent_unit),
+    }
+}
+
+// line number of the first match, comments are left out
+pub fn find_line(filename: &str, pattern: &str) -> usize {
+    let Ok(re) = Regex::new(&format!("(?i){}", pattern)) else {
+        return 0;
+    };
+    let Ok(bytes) = fs::read(filename) else {
+        return 0;
+    };
+    let content = String::from_utf8_lossy(&bytes);
+    for (i, line) in content.lines().enumerate() {
+        let code = line.split("--").next().unwrap_or("");
+        let code = code.split("//").next().unwrap_or("");
+        if re.is_match(code) {
+            return i + 1;
+        }
+    }
+    0
+}
+
+fn location(filename: &str, line: usize) -> String {
+    if line == 0 {
+        filename.to_string()
+    } else {
+        format!("{}:{}", filename, line)
+    }
+}
+
+pub fn chain_lines(top: &Element, chain: &[Dependency]) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let top_line = find_line(&top.filename, &unit_pattern(&top.unit));
+    lines.push(format!("{}.{}  ({})", top.library, top.unit, location(&top.filename, top_line)));
+    let mut parent_unit = top.unit.clone();
+    let mut parent_file = top.filename.clone();
+    for dep in chain {
+        let text = match dep.kind.as_str() {
+            "instance" => format!("{}: {}.{}", dep.label, dep.library, dep.unit),
+            "use" => format!("use {}", dep.label),
+            _ => format!("{}.{}", dep.library, dep.unit),
+        };
+        let line = find_line(&parent_file, &dependency_pattern(&parent_unit, dep));
+        lines.push(format!("-> {}  ({})", text, location(&parent_file, line)));
+        parent_unit = dep.unit.clone();
+        if !dep.filename.is_empty() {
+            parent_file = dep.filename.clone();
+        }
+    }
+    lines
+}
+
+pub fn print_why(
+    toplevel: String, libraries_toml_filename: &String, tool_toml_filename: &String,
+    replacements: &HashMap<String, String>, overrides: &BindingOverrides, target: &str,
+) {
+    let (element_list, _) = get_single_element_list(&toplevel, libraries_toml_filename, tool_toml_filename, replacements, overrides);
+    let Some(top) = element_list.last() else {
+        eprintln!("No files found!");
+        exit(1);
+    };
+    match find_chain(&element_list, target) {
+        None => {
+            eprintln!("'{}' isn't needed by {}", target, toplevel);
+            exit(1);
+        }
+        Some(chain) => println!("{}", chain_lines(top, &chain).join("\n")),
+    }
+}